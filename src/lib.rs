@@ -0,0 +1,34 @@
+//! # Toy Virtual Machine
+//! A toy virtual machine with von Neumann architecture.
+//!
+//! The machine is driven by a [Controller], which owns the
+//! [State] of the machine. The state is backed by a
+//! [Memory] device that stores both the program
+//! and the [Register]s: a shared [VirtualMemory] mapping,
+//! a private [CopyOnWriteMemory] mapping or a [BufferMemory].
+//!
+//! ```no_run
+//! use toy_vmachine::{Controller, State, VirtualMemory};
+//!
+//! let memory = VirtualMemory::new("images/hello_world.bin".as_ref()).unwrap();
+//! let state = State::new(memory).unwrap();
+//! let mut controller = Controller::new(state);
+//! controller.execute().unwrap();
+//! ```
+
+pub mod vm;
+
+pub use vm::arch::instruction::{decode, Instruction};
+pub use vm::arch::ARCH_BYTES;
+pub use vm::components::controller::{Controller, ControllerBuilder, Limit, Outcome};
+pub use vm::components::devices::{
+    ConsoleDevice, Device, IntervalTimerDevice, RngDevice, TimerDevice,
+};
+pub use vm::components::display::{
+    Display, FileDisplay, NullDisplay, ScriptedDisplay, SharedDisplay, SharedOutput, StreamDisplay,
+    SystemDisplay,
+};
+pub use vm::components::memory::{BufferMemory, CopyOnWriteMemory, Memory, VirtualMemory};
+pub use vm::components::state::{Flag, Register, StackBounds, State};
+pub use vm::components::syscalls::SyscallHandler;
+pub use vm::error::{Fault, VmError};
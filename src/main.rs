@@ -1,20 +1,277 @@
-use crate::vm::components::controller::Controller;
-use crate::vm::components::memory::VirtualMemory;
-use vm::components::state::State;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
+use toy_vmachine::vm::components::devices::{attach_standard_devices, DEFAULT_MMIO_BASE};
+use toy_vmachine::vm::components::tracer::{JsonTracer, TextTracer, Tracer};
+use toy_vmachine::vm::tools::assembler::assemble;
+use toy_vmachine::vm::tools::debugger::{Debugger, StdinLines};
+use toy_vmachine::vm::tools::disassembler::disassemble;
+use toy_vmachine::vm::tools::parse_number;
+use toy_vmachine::{
+    Controller, CopyOnWriteMemory, Limit, Outcome, Register, SharedDisplay, StackBounds, State,
+    SystemDisplay, VirtualMemory,
+};
 
-pub mod vm;
-
-use clap::Parser;
+/// Exit code of `run` when the program runs out of a limit.
+const LIMIT_EXCEEDED_CODE: u8 = 2;
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
-    image_path: std::path::PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    run: Option<ImageArgs>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the image (the default command)
+    Run(ImageArgs),
+    /// Run the image in the interactive debugger
+    Debug(ImageArgs),
+    /// Disassemble the image starting from the initial IP
+    Disasm { image_path: PathBuf },
+    /// Assemble a source file into an image
+    Asm {
+        source_path: PathBuf,
+        /// Path of the image, the source path with `.bin` extension by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct ImageArgs {
+    image_path: PathBuf,
+
+    /// Write changes made by the program back to the image
+    #[arg(long, overrides_with = "no_persist")]
+    persist: bool,
+
+    /// Run the program on a private copy of the image (default)
+    #[arg(long, overrides_with = "persist")]
+    no_persist: bool,
+
+    /// Write the trace of the execution to the file
+    #[arg(long, value_name = "PATH")]
+    trace: Option<PathBuf>,
+
+    /// Format of the trace
+    #[arg(long, value_enum, default_value_t = TraceFormat::Text)]
+    trace_format: TraceFormat,
+
+    /// Lowest address of the stack, the initial SP by default
+    #[arg(long, value_name = "ADDR", value_parser = parse_u32)]
+    stack_base: Option<u32>,
+
+    /// Size of the stack in bytes. By default the stack is bounded as laid out
+    /// by the image, or by the end of the memory if `--stack-base` is given
+    #[arg(long, value_name = "BYTES", value_parser = parse_u32)]
+    stack_size: Option<u32>,
+
+    /// Address of the memory-mapped devices: the console,
+    /// the timer and the random number generator
+    #[arg(long, value_name = "ADDR", value_parser = parse_u32, default_value_t = DEFAULT_MMIO_BASE)]
+    mmio_base: u32,
+
+    /// Stop after executing the given number of instructions
+    #[arg(long, value_name = "N")]
+    max_steps: Option<u64>,
+
+    /// Stop after the given number of seconds, e.g. `0.5`
+    #[arg(long, value_name = "SECONDS", value_parser = parse_seconds)]
+    timeout: Option<Duration>,
+}
+
+fn parse_seconds(text: &str) -> Result<Duration, String> {
+    text.parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("{:?} is not a number of seconds", text))
+}
+
+fn parse_u32(text: &str) -> Result<u32, String> {
+    parse_number(text).ok_or_else(|| format!("{:?} is not a 32-bit number", text))
+}
+
+#[derive(Copy, Clone, ValueEnum)]
+enum TraceFormat {
+    /// Human-readable text
+    Text,
+    /// JSON Lines, one object per step
+    Json,
+}
+
+impl ImageArgs {
+    fn load_controller(&self) -> Option<Controller> {
+        // The console device and the I/O instructions read the same stdin
+        let display = SharedDisplay::new(SystemDisplay::new());
+        let state = load_state(&self.image_path, self.persist).and_then(|mut state| {
+            attach_standard_devices(&mut state, self.mmio_base, display.clone())
+                .map_err(|fault| io::Error::new(io::ErrorKind::InvalidInput, fault))?;
+            Ok(state)
+        });
+        let mut controller = match state {
+            Ok(mut state) => {
+                let memory_size = state.get_memory_handler().size();
+                state.set_stack_bounds(self.stack_bounds(state.stack_bounds(), memory_size));
+                let mut controller = Controller::builder(state).display(display).build();
+                controller.set_max_steps(self.max_steps);
+                controller.set_timeout(self.timeout);
+                controller
+            }
+            Err(error) => {
+                eprintln!(
+                    "Couldn't load image {}: {}",
+                    self.image_path.display(),
+                    error
+                );
+                return None;
+            }
+        };
+        if let Some(trace_path) = &self.trace {
+            match self.create_tracer(trace_path) {
+                Ok(tracer) => controller.set_tracer(tracer),
+                Err(error) => {
+                    eprintln!("Couldn't create trace {}: {}", trace_path.display(), error);
+                    return None;
+                }
+            }
+        }
+        Some(controller)
+    }
+
+    fn stack_bounds(&self, default: StackBounds, memory_size: u32) -> StackBounds {
+        let base = self.stack_base.unwrap_or(default.base);
+        let limit = match (self.stack_size, self.stack_base) {
+            (Some(size), _) => base.saturating_add(size),
+            (None, Some(_)) => memory_size,
+            (None, None) => default.limit,
+        };
+        StackBounds { base, limit }
+    }
+
+    fn create_tracer(&self, trace_path: &Path) -> io::Result<Box<dyn Tracer>> {
+        let output = BufWriter::new(File::create(trace_path)?);
+        Ok(match self.trace_format {
+            TraceFormat::Text => Box::new(TextTracer::new(output)),
+            TraceFormat::Json => Box::new(JsonTracer::new(output)),
+        })
+    }
+}
+
+fn load_state(image_path: &Path, persist: bool) -> io::Result<State> {
+    let state = if persist {
+        State::new(VirtualMemory::new(image_path)?)
+    } else {
+        State::new(CopyOnWriteMemory::new(image_path)?)
+    };
+    state.map_err(|fault| io::Error::new(io::ErrorKind::InvalidData, fault))
+}
+
+/// Exit code of `run` for the exit syscall. Codes that don't fit into a byte
+/// become 1 instead of being truncated, so a failure never looks like success.
+fn exit_code(code: u32) -> u8 {
+    u8::try_from(code).unwrap_or(1)
+}
+
+fn run(args: &ImageArgs) -> ExitCode {
+    let Some(mut controller) = args.load_controller() else {
+        return ExitCode::FAILURE;
+    };
+    match controller.execute() {
+        Ok(Outcome::Finished) => ExitCode::SUCCESS,
+        Ok(Outcome::Exited { code }) => ExitCode::from(exit_code(code)),
+        Ok(Outcome::LimitExceeded { limit, steps }) => {
+            let limit = match limit {
+                Limit::Steps => "step limit",
+                Limit::Time => "timeout",
+            };
+            eprintln!("Limit exceeded: {} after {} steps", limit, steps);
+            ExitCode::from(LIMIT_EXCEEDED_CODE)
+        }
+        Err(error) => {
+            eprintln!("Machine fault: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn debug(args: &ImageArgs) -> ExitCode {
+    let Some(controller) = args.load_controller() else {
+        return ExitCode::FAILURE;
+    };
+    let mut debugger = Debugger::new(controller, StdinLines::new(), io::stdout());
+    match debugger.run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Debugger failure: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn disasm(image_path: &Path) -> ExitCode {
+    let state = match load_state(image_path, false) {
+        Ok(state) => state,
+        Err(error) => {
+            eprintln!("Couldn't load image {}: {}", image_path.display(), error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let ip_value = state.register_value(Register::IP);
+    for word in disassemble(state.get_memory_handler(), ip_value) {
+        println!("{}", word);
+    }
+    ExitCode::SUCCESS
+}
+
+fn asm(source_path: &Path, output: Option<PathBuf>) -> ExitCode {
+    let source = match fs::read_to_string(source_path) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("Couldn't read {}: {}", source_path.display(), error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let image = match assemble(&source) {
+        Ok(image) => image,
+        Err(error) => {
+            eprintln!("{}:{}", source_path.display(), error);
+            return ExitCode::FAILURE;
+        }
+    };
+    let output = output.unwrap_or_else(|| source_path.with_extension("bin"));
+    if let Err(error) = fs::write(&output, image) {
+        eprintln!("Couldn't write {}: {}", output.display(), error);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
 }
 
-fn main() {
-    let args = Cli::parse();
-    let memory = VirtualMemory::new(&args.image_path);
-    let state = State::new(memory);
-    let mut controller = Controller::new(state);
-    controller.execute();
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match (cli.command, cli.run) {
+        (Some(Command::Run(args)), _) | (None, Some(args)) => run(&args),
+        (Some(Command::Debug(args)), _) => debug(&args),
+        (Some(Command::Disasm { image_path }), _) => disasm(&image_path),
+        (
+            Some(Command::Asm {
+                source_path,
+                output,
+            }),
+            _,
+        ) => asm(&source_path, output),
+        (None, None) => {
+            eprintln!("No image given, see --help");
+            ExitCode::FAILURE
+        }
+    }
 }
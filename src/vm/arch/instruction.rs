@@ -1,7 +1,8 @@
 use crate::vm::components::controller::Controller;
-use crate::vm::components::state::Register;
+use crate::vm::components::state::{Flag, Register, State};
+use crate::vm::error::Fault;
 use byteorder::{ByteOrder, LittleEndian};
-use num::ToPrimitive;
+use std::fmt;
 
 use crate::vm::utils::instruction_macro::register_instructions;
 
@@ -29,16 +30,73 @@ register_instructions! {
     0x15 => PushToStackInstruction,
     0x16 => PopFromStackInstruction,
     0x17 => CallInstruction,
-    0x18 => RetInstruction
+    0x18 => RetInstruction,
+    0x19 => StoreByteInstruction,
+    0x1A => StoreWordInstruction,
+    0x1B => LoadByteInstruction,
+    0x1C => LoadWordInstruction,
+    0x1D => DerefInstruction,
+    0x1E => AndInstruction,
+    0x1F => OrInstruction,
+    0x20 => XorInstruction,
+    0x21 => NotInstruction,
+    0x22 => ShiftLeftInstruction,
+    0x23 => ShiftRightInstruction,
+    0x24 => ShiftArithmeticRightInstruction,
+    0x25 => SignedDivInstruction,
+    0x26 => SignedModInstruction,
+    0x27 => SignedLessInstruction,
+    0x28 => SignedLessEqualInstruction,
+    0x29 => SignedGreaterInstruction,
+    0x2A => SignedGreaterEqualInstruction,
+    0x2B => OutSignedNumberInstruction,
+    0x2C => InputSignedNumberInstruction,
+    0x2D => JumpZeroInstruction,
+    0x2E => JumpNotZeroInstruction,
+    0x2F => JumpCarryInstruction,
+    0x30 => JumpNotCarryInstruction,
+    0x31 => JumpOverflowInstruction,
+    0x32 => JumpNotOverflowInstruction,
+    0x33 => JumpSignInstruction,
+    0x34 => JumpNotSignInstruction,
+    0x35 => ModInstruction,
+    0x36 => MulHighInstruction,
+    0x37 => AddImmediateInstruction,
+    0x38 => SubImmediateInstruction,
+    0x39 => CompareImmediateInstruction,
+    0x3A => LoadUpperImmediateInstruction,
+    0x3B => LoadLowerImmediateInstruction,
+    0x3C => JumpRegisterInstruction,
+    0x3D => CallRegisterInstruction,
+    0x3E => LoadStackInstruction,
+    0x3F => StoreStackInstruction,
+    0x40 => LoadFrameInstruction,
+    0x41 => StoreFrameInstruction,
+    0x42 => EnterInstruction,
+    0x43 => LeaveInstruction,
+    0x44 => EnableInterruptsInstruction,
+    0x45 => DisableInterruptsInstruction,
+    0x46 => InterruptReturnInstruction,
+    0x47 => SyscallInstruction
+}
+
+/// Sets FLAGS as if [right] was subtracted from [left],
+/// this is how comparisons report their results.
+fn set_compare_flags(state: &mut State, left: u32, right: u32) {
+    let (result, carry) = left.overflowing_sub(right);
+    let (_, overflow) = (left as i32).overflowing_sub(right as i32);
+    state.set_flags(result, carry, overflow);
 }
 
 /// # Trait *Instruction*
 /// Represents instruction of the virtual machine.
-/// [execute] method is responsible for executing the instruction.
+/// [execute] method is responsible for executing the instruction
+/// and reports a [Fault] if the instruction can't be completed.
 /// [move_ip] returns true, if after instruction execution the ip
 /// register needs to be incremented.
-pub trait Instruction {
-    fn execute(&mut self, controller: &mut Controller);
+/// Instructions are displayed in their mnemonic form, e.g. `ADD R0, R1, R2`.
+pub trait Instruction: fmt::Display {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault>;
     fn move_ip(&self) -> bool {
         true
     }
@@ -53,7 +111,7 @@ pub trait Instruction {
 /// - 3rd byte: [second_register] address
 /// - 4th byte: [third_register] address
 ///
-/// Result is stored in the [third_register]
+/// Result is stored in the [third_register], FLAGS are updated
 ///
 pub struct AddInstruction {
     first_register: Register,
@@ -62,22 +120,35 @@ pub struct AddInstruction {
 }
 
 impl AddInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        AddInstruction {
-            first_register: Register::from_addr(code[1] as u32),
-            second_register: Register::from_addr(code[2] as u32),
-            third_register: Register::from_addr(code[3] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(AddInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
     }
 }
 
 impl Instruction for AddInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         let state = controller.mut_state();
         let first_value = state.register_value(self.first_register);
         let second_value = state.register_value(self.second_register);
-        let (result, _overflow_flag) = first_value.overflowing_add(second_value);
+        let (result, carry) = first_value.overflowing_add(second_value);
+        let (_, overflow) = (first_value as i32).overflowing_add(second_value as i32);
         state.set_register_value(self.third_register, result);
+        state.set_flags(result, carry, overflow);
+        Ok(())
+    }
+}
+
+impl fmt::Display for AddInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ADD {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
     }
 }
 
@@ -91,7 +162,7 @@ impl Instruction for AddInstruction {
 /// - 3rd byte: [second_register] address
 /// - 4th byte: [third_register] address
 ///
-/// Result is stored in the [third_register]
+/// Result is stored in the [third_register], FLAGS are updated
 ///
 pub struct SubInstruction {
     first_register: Register,
@@ -100,22 +171,35 @@ pub struct SubInstruction {
 }
 
 impl SubInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        SubInstruction {
-            first_register: Register::from_addr(code[1] as u32),
-            second_register: Register::from_addr(code[2] as u32),
-            third_register: Register::from_addr(code[3] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(SubInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
     }
 }
 
 impl Instruction for SubInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         let state = controller.mut_state();
         let first_value = state.register_value(self.first_register);
         let second_value = state.register_value(self.second_register);
-        let (result, _overflow_flag) = first_value.overflowing_sub(second_value);
+        let (result, carry) = first_value.overflowing_sub(second_value);
+        let (_, overflow) = (first_value as i32).overflowing_sub(second_value as i32);
         state.set_register_value(self.third_register, result);
+        state.set_flags(result, carry, overflow);
+        Ok(())
+    }
+}
+
+impl fmt::Display for SubInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SUB {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
     }
 }
 
@@ -129,7 +213,7 @@ impl Instruction for SubInstruction {
 /// - 3rd byte: [second_register] address
 /// - 4th byte: [third_register] address
 ///
-/// Result is stored in the [third_register]
+/// Result is stored in the [third_register], FLAGS are updated
 ///
 pub struct MulInstruction {
     first_register: Register,
@@ -138,22 +222,35 @@ pub struct MulInstruction {
 }
 
 impl MulInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        MulInstruction {
-            first_register: Register::from_addr(code[1] as u32),
-            second_register: Register::from_addr(code[2] as u32),
-            third_register: Register::from_addr(code[3] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(MulInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
     }
 }
 
 impl Instruction for MulInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         let state = controller.mut_state();
         let first_value = state.register_value(self.first_register);
         let second_value = state.register_value(self.second_register);
-        let (result, _overflow_flag) = first_value.overflowing_mul(second_value);
+        let (result, carry) = first_value.overflowing_mul(second_value);
+        let (_, overflow) = (first_value as i32).overflowing_mul(second_value as i32);
         state.set_register_value(self.third_register, result);
+        state.set_flags(result, carry, overflow);
+        Ok(())
+    }
+}
+
+impl fmt::Display for MulInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MUL {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
     }
 }
 
@@ -176,28 +273,38 @@ pub struct DivInstruction {
 }
 
 impl DivInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        DivInstruction {
-            first_register: Register::from_addr(code[1] as u32),
-            second_register: Register::from_addr(code[2] as u32),
-            third_register: Register::from_addr(code[3] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(DivInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
     }
 }
 
 impl Instruction for DivInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         let state = controller.mut_state();
 
         let first_value = state.register_value(self.first_register);
         let second_value = state.register_value(self.second_register);
 
-        assert_ne!(
-            second_value, 0,
-            "DivInstruction failure: second register = 0"
-        );
+        if second_value == 0 {
+            return Err(Fault::DivideByZero);
+        }
         let (result, _overflow_flag) = first_value.overflowing_div(second_value);
         state.set_register_value(self.third_register, result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for DivInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "DIV {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
     }
 }
 
@@ -216,16 +323,17 @@ pub struct JumpInstruction {
 }
 
 impl JumpInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        JumpInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpInstruction {
             offset: LittleEndian::read_i16(&code[1..=2]),
-        }
+        })
     }
 }
 
 impl Instruction for JumpInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         controller.jump(self.offset);
+        Ok(())
     }
 
     fn move_ip(&self) -> bool {
@@ -233,6 +341,12 @@ impl Instruction for JumpInstruction {
     }
 }
 
+impl fmt::Display for JumpInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JMP {}", self.offset)
+    }
+}
+
 /// # LoadInstruction
 /// Loads value from address [ip + offset],
 /// [offset] is parsed as i16 little endian
@@ -251,32 +365,31 @@ pub struct LoadInstruction {
 }
 
 impl LoadInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        LoadInstruction {
-            register: Register::from_addr(code[1] as u32),
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(LoadInstruction {
+            register: Register::from_addr(code[1] as u32)?,
             offset: LittleEndian::read_i16(&code[2..=3]),
-        }
+        })
     }
 }
 
 impl Instruction for LoadInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
-        let ip_value = controller
-            .state()
-            .register_value(Register::IP)
-            .to_i32()
-            .expect("Couldn't convert ip register to i32");
-        let address = ip_value + (self.offset as i32);
-        let address = address.to_u32().expect("Couldn't convert address to u32");
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let ip_value = controller.state().register_value(Register::IP);
+        let address = ip_value.wrapping_add_signed(self.offset as i32);
 
-        let value = controller
-            .mut_state()
-            .get_memory_handler()
-            .read_byte(address) as u32;
+        let value = controller.state().read_byte(address)? as u32;
 
         controller
             .mut_state()
             .set_register_value(self.register, value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for LoadInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LD {}, {}", self.register, self.offset)
     }
 }
 
@@ -293,14 +406,15 @@ impl Instruction for LoadInstruction {
 pub struct FinishInstruction;
 
 impl FinishInstruction {
-    pub fn new(_code: &[u8]) -> Self {
-        FinishInstruction {}
+    pub fn new(_code: &[u8]) -> Result<Self, Fault> {
+        Ok(FinishInstruction {})
     }
 }
 
 impl Instruction for FinishInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         controller.mut_state().set_register_value(Register::END, 1);
+        Ok(())
     }
 
     fn move_ip(&self) -> bool {
@@ -308,6 +422,12 @@ impl Instruction for FinishInstruction {
     }
 }
 
+impl fmt::Display for FinishInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FIN")
+    }
+}
+
 /// # OutInstruction
 /// Prints string from the address, which is stored
 /// in a given register.
@@ -323,32 +443,40 @@ pub struct OutInstruction {
 }
 
 impl OutInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        OutInstruction {
-            register: Register::from_addr(code[1] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(OutInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+        })
     }
 }
 
 impl Instruction for OutInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         let mut address = controller.state().register_value(self.register);
         loop {
-            let char = controller.state().get_memory_handler().read_byte(address) as char;
+            let char = controller.state().read_byte(address)? as char;
 
             if char == '\0' {
                 break;
             }
 
-            controller.display().print(char);
-            address += 1;
+            controller.print(char)?;
+            address = address.wrapping_add(1);
         }
+        Ok(())
+    }
+}
+
+impl fmt::Display for OutInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OUT {}", self.register)
     }
 }
 
 /// # EqualInstruction
 /// Compares values in [left] and [right] registers
 /// and stores 1 to CMP if they are equal. Otherwise, stores 0.
+/// FLAGS are set as if right was subtracted from left.
 ///
 /// Structure:
 /// - 1st byte: instruction code
@@ -362,21 +490,29 @@ pub struct EqualInstruction {
 }
 
 impl EqualInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        EqualInstruction {
-            left: Register::from_addr(code[1] as u32),
-            right: Register::from_addr(code[2] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(EqualInstruction {
+            left: Register::from_addr(code[1] as u32)?,
+            right: Register::from_addr(code[2] as u32)?,
+        })
     }
 }
 
 impl Instruction for EqualInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         let left_value = controller.state().register_value(self.left);
         let right_value = controller.state().register_value(self.right);
+        set_compare_flags(controller.mut_state(), left_value, right_value);
         controller
             .mut_state()
             .set_register_value(Register::CMP, (left_value == right_value) as u32);
+        Ok(())
+    }
+}
+
+impl fmt::Display for EqualInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EQ {}, {}", self.left, self.right)
     }
 }
 
@@ -384,6 +520,7 @@ impl Instruction for EqualInstruction {
 /// Compares values in [left] and [right] registers
 /// and stores 1 to CMP if left < right.
 /// Otherwise, stores 0.
+/// FLAGS are set as if right was subtracted from left.
 ///
 /// Structure:
 /// - 1st byte: instruction code
@@ -397,21 +534,29 @@ pub struct LessInstruction {
 }
 
 impl LessInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        LessInstruction {
-            left: Register::from_addr(code[1] as u32),
-            right: Register::from_addr(code[2] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(LessInstruction {
+            left: Register::from_addr(code[1] as u32)?,
+            right: Register::from_addr(code[2] as u32)?,
+        })
     }
 }
 
 impl Instruction for LessInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         let left_value = controller.state().register_value(self.left);
         let right_value = controller.state().register_value(self.right);
+        set_compare_flags(controller.mut_state(), left_value, right_value);
         controller
             .mut_state()
             .set_register_value(Register::CMP, (left_value < right_value) as u32);
+        Ok(())
+    }
+}
+
+impl fmt::Display for LessInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "L {}, {}", self.left, self.right)
     }
 }
 
@@ -419,6 +564,7 @@ impl Instruction for LessInstruction {
 /// Compares values in [left] and [right] registers
 /// and stores 1 to CMP if left <= right.
 /// Otherwise, stores 0.
+/// FLAGS are set as if right was subtracted from left.
 ///
 /// Structure:
 /// - 1st byte: instruction code
@@ -432,21 +578,29 @@ pub struct LessEqualInstruction {
 }
 
 impl LessEqualInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        LessEqualInstruction {
-            left: Register::from_addr(code[1] as u32),
-            right: Register::from_addr(code[2] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(LessEqualInstruction {
+            left: Register::from_addr(code[1] as u32)?,
+            right: Register::from_addr(code[2] as u32)?,
+        })
     }
 }
 
 impl Instruction for LessEqualInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         let left_value = controller.state().register_value(self.left);
         let right_value = controller.state().register_value(self.right);
+        set_compare_flags(controller.mut_state(), left_value, right_value);
         controller
             .mut_state()
             .set_register_value(Register::CMP, (left_value <= right_value) as u32);
+        Ok(())
+    }
+}
+
+impl fmt::Display for LessEqualInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LE {}, {}", self.left, self.right)
     }
 }
 
@@ -466,25 +620,33 @@ pub struct LoadAbsoluteInstruction {
 }
 
 impl LoadAbsoluteInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        LoadAbsoluteInstruction {
-            register: Register::from_addr(code[1] as u32),
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(LoadAbsoluteInstruction {
+            register: Register::from_addr(code[1] as u32)?,
             value: LittleEndian::read_u16(&code[2..=3]) as u32,
-        }
+        })
     }
 }
 
 impl Instruction for LoadAbsoluteInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         controller
             .mut_state()
             .set_register_value(self.register, self.value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for LoadAbsoluteInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LDA {}, {:#x}", self.register, self.value)
     }
 }
 
 /// # InputInstruction
 /// Gets a character from the user
 /// and stores it in [register].
+/// The end of an input line is read as '\n'.
 ///
 /// Structure:
 /// - 1st byte: instruction code
@@ -497,19 +659,26 @@ pub struct InputInstruction {
 }
 
 impl InputInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        InputInstruction {
-            register: Register::from_addr(code[1] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(InputInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+        })
     }
 }
 
 impl Instruction for InputInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
-        let c = controller.mut_display().get();
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let c = controller.mut_display().get()?;
         controller
             .mut_state()
             .set_register_value(self.register, c as u32);
+        Ok(())
+    }
+}
+
+impl fmt::Display for InputInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "INP {}", self.register)
     }
 }
 
@@ -529,25 +698,22 @@ pub struct JumpCompareInstruction {
 }
 
 impl JumpCompareInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        JumpCompareInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpCompareInstruction {
             offset: LittleEndian::read_i16(&code[1..=2]),
             success: true,
-        }
+        })
     }
 }
 
 impl Instruction for JumpCompareInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         if controller.state().register_value(Register::CMP) == 0 {
             self.success = false;
-            return;
+            return Ok(());
         }
-        let ip_value = controller.state().register_value(Register::IP);
-        let address = (ip_value as i32 + self.offset as i32) as u32;
-        controller
-            .mut_state()
-            .set_register_value(Register::IP, address);
+        controller.jump(self.offset);
+        Ok(())
     }
 
     fn move_ip(&self) -> bool {
@@ -555,6 +721,12 @@ impl Instruction for JumpCompareInstruction {
     }
 }
 
+impl fmt::Display for JumpCompareInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JCMP {}", self.offset)
+    }
+}
+
 /// # JumpNotCompareInstruction
 /// Moves [IP] register by the [offset] if [CMP] flag
 /// is zero [offset] is parsed as little endian i16
@@ -571,25 +743,22 @@ pub struct JumpNotCompareInstruction {
 }
 
 impl JumpNotCompareInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        JumpNotCompareInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpNotCompareInstruction {
             offset: LittleEndian::read_i16(&code[1..=2]),
             success: true,
-        }
+        })
     }
 }
 
 impl Instruction for JumpNotCompareInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         if controller.state().register_value(Register::CMP) != 0 {
             self.success = false;
-            return;
+            return Ok(());
         }
-        let ip_value = controller.state().register_value(Register::IP);
-        let address = (ip_value as i32 + self.offset as i32) as u32;
-        controller
-            .mut_state()
-            .set_register_value(Register::IP, address);
+        controller.jump(self.offset);
+        Ok(())
     }
 
     fn move_ip(&self) -> bool {
@@ -597,6 +766,12 @@ impl Instruction for JumpNotCompareInstruction {
     }
 }
 
+impl fmt::Display for JumpNotCompareInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JNCMP {}", self.offset)
+    }
+}
+
 /// # OutFromRegisterInstruction
 /// Prints char that is stored in the [register]
 ///
@@ -611,18 +786,25 @@ pub struct OutFromRegisterInstruction {
 }
 
 impl OutFromRegisterInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        OutFromRegisterInstruction {
-            register: Register::from_addr(code[1] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(OutFromRegisterInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+        })
     }
 }
 
 impl Instruction for OutFromRegisterInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
-        let value = char::from_u32(controller.state().register_value(self.register))
-            .expect("Expected char to be in register");
-        controller.display().print(value);
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let value = controller.state().register_value(self.register);
+        let value = char::from_u32(value).ok_or(Fault::InvalidCharacter(value))?;
+        controller.print(value)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for OutFromRegisterInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OUTR {}", self.register)
     }
 }
 
@@ -638,14 +820,20 @@ impl Instruction for OutFromRegisterInstruction {
 pub struct SkipInstruction {}
 
 impl SkipInstruction {
-    pub fn new(_code: &[u8]) -> Self {
-        SkipInstruction {}
+    pub fn new(_code: &[u8]) -> Result<Self, Fault> {
+        Ok(SkipInstruction {})
     }
 }
 
 impl Instruction for SkipInstruction {
-    fn execute(&mut self, _controller: &mut Controller) {
-        return;
+    fn execute(&mut self, _controller: &mut Controller) -> Result<(), Fault> {
+        Ok(())
+    }
+}
+
+impl fmt::Display for SkipInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SKIP")
     }
 }
 
@@ -662,20 +850,27 @@ pub struct OutNumberInstruction {
 }
 
 impl OutNumberInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        OutNumberInstruction {
-            register: Register::from_addr(code[1] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(OutNumberInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+        })
     }
 }
 
 impl Instruction for OutNumberInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         let number = controller.state().register_value(self.register);
         let number_str = number.to_string();
         for c in number_str.chars() {
-            controller.display().print(c);
+            controller.print(c)?;
         }
+        Ok(())
+    }
+}
+
+impl fmt::Display for OutNumberInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OUTN {}", self.register)
     }
 }
 
@@ -694,20 +889,27 @@ pub struct MoveInstruction {
 }
 
 impl MoveInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        MoveInstruction {
-            first_register: Register::from_addr(code[1] as u32),
-            second_register: Register::from_addr(code[2] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(MoveInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+        })
     }
 }
 
 impl Instruction for MoveInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
         let value = controller.state().register_value(self.first_register);
         controller
             .mut_state()
             .set_register_value(self.second_register, value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for MoveInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MOV {}, {}", self.first_register, self.second_register)
     }
 }
 
@@ -725,19 +927,26 @@ pub struct InputNumberInstruction {
 }
 
 impl InputNumberInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        InputNumberInstruction {
-            register: Register::from_addr(code[1] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(InputNumberInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+        })
     }
 }
 
 impl Instruction for InputNumberInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
-        let num = controller.mut_display().get_num();
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let num = controller.mut_display().get_num()?;
         controller
             .mut_state()
             .set_register_value(self.register, num);
+        Ok(())
+    }
+}
+
+impl fmt::Display for InputNumberInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "INPN {}", self.register)
     }
 }
 
@@ -755,16 +964,22 @@ pub struct PushToStackInstruction {
 }
 
 impl PushToStackInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        PushToStackInstruction {
-            register: Register::from_addr(code[1] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(PushToStackInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+        })
     }
 }
 
 impl Instruction for PushToStackInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
-        controller.mut_state().push_to_stack(self.register);
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        controller.mut_state().push_to_stack(self.register)
+    }
+}
+
+impl fmt::Display for PushToStackInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PUSH {}", self.register)
     }
 }
 
@@ -782,16 +997,22 @@ pub struct PopFromStackInstruction {
 }
 
 impl PopFromStackInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        PopFromStackInstruction {
-            register: Register::from_addr(code[1] as u32),
-        }
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(PopFromStackInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+        })
     }
 }
 
 impl Instruction for PopFromStackInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
-        controller.mut_state().pop_from_stack(self.register);
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        controller.mut_state().pop_from_stack(self.register)
+    }
+}
+
+impl fmt::Display for PopFromStackInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "POP {}", self.register)
     }
 }
 
@@ -809,17 +1030,18 @@ pub struct CallInstruction {
 }
 
 impl CallInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        CallInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(CallInstruction {
             offset: LittleEndian::read_i16(&code[1..=2]),
-        }
+        })
     }
 }
 
 impl Instruction for CallInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
-        controller.mut_state().push_to_stack(Register::IP);
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        controller.mut_state().push_to_stack(Register::IP)?;
         controller.jump(self.offset);
+        Ok(())
     }
 
     fn move_ip(&self) -> bool {
@@ -827,6 +1049,12 @@ impl Instruction for CallInstruction {
     }
 }
 
+impl fmt::Display for CallInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CALL {}", self.offset)
+    }
+}
+
 /// RetInstruction
 /// Instruction that returns IP register to the value
 /// for the stack. Should be used only when [CallInstruction]
@@ -840,24 +1068,206 @@ impl Instruction for CallInstruction {
 pub struct RetInstruction {}
 
 impl RetInstruction {
-    pub fn new(_code: &[u8]) -> Self {
-        RetInstruction {}
+    pub fn new(_code: &[u8]) -> Result<Self, Fault> {
+        Ok(RetInstruction {})
     }
 }
 
 impl Instruction for RetInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
-        controller.mut_state().pop_from_stack(Register::IP);
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        controller.mut_state().pop_from_stack(Register::IP)
+    }
+}
+
+impl fmt::Display for RetInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RET")
+    }
+}
+
+/// # StoreByteInstruction
+/// Stores the lowest byte of [source] register
+/// to the address [base + offset].
+/// [offset] is parsed as i8, so with zero offset
+/// the address is taken from [base] register as is.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [source] register address
+/// - 3rd byte: [base] register address
+/// - 4th byte: offset
+///
+pub struct StoreByteInstruction {
+    source: Register,
+    base: Register,
+    offset: i8,
+}
+
+impl StoreByteInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(StoreByteInstruction {
+            source: Register::from_addr(code[1] as u32)?,
+            base: Register::from_addr(code[2] as u32)?,
+            offset: code[3] as i8,
+        })
+    }
+}
+
+impl Instruction for StoreByteInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let address = controller.state().register_value(self.base);
+        let address = address.wrapping_add_signed(self.offset as i32);
+        let value = controller.state().register_value(self.source) as u8;
+        controller.mut_state().write_byte(address, value)
+    }
+}
+
+impl fmt::Display for StoreByteInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "STB {}, {}, {}", self.source, self.base, self.offset)
     }
 }
 
-/// DerefInstruction
+/// # StoreWordInstruction
+/// Stores the value of [source] register
+/// to the address [base + offset].
+/// [offset] is parsed as i8, the address has to be aligned.
 ///
 /// Structure:
 /// - 1st byte: instruction code
-/// - 2nd byte: destination register address
-/// - 3rd byte: source register address to dereference
+/// - 2nd byte: [source] register address
+/// - 3rd byte: [base] register address
 /// - 4th byte: offset
+///
+pub struct StoreWordInstruction {
+    source: Register,
+    base: Register,
+    offset: i8,
+}
+
+impl StoreWordInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(StoreWordInstruction {
+            source: Register::from_addr(code[1] as u32)?,
+            base: Register::from_addr(code[2] as u32)?,
+            offset: code[3] as i8,
+        })
+    }
+}
+
+impl Instruction for StoreWordInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let address = controller.state().register_value(self.base);
+        let address = address.wrapping_add_signed(self.offset as i32);
+        let value = controller.state().register_value(self.source);
+        controller.mut_state().write_word(address, value)
+    }
+}
+
+impl fmt::Display for StoreWordInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "STW {}, {}, {}", self.source, self.base, self.offset)
+    }
+}
+
+/// # LoadByteInstruction
+/// Loads a byte from the address [base + offset]
+/// to [dest] register.
+/// [offset] is parsed as i8.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [dest] register address
+/// - 3rd byte: [base] register address
+/// - 4th byte: offset
+///
+pub struct LoadByteInstruction {
+    dest: Register,
+    base: Register,
+    offset: i8,
+}
+
+impl LoadByteInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(LoadByteInstruction {
+            dest: Register::from_addr(code[1] as u32)?,
+            base: Register::from_addr(code[2] as u32)?,
+            offset: code[3] as i8,
+        })
+    }
+}
+
+impl Instruction for LoadByteInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let address = controller.state().register_value(self.base);
+        let address = address.wrapping_add_signed(self.offset as i32);
+        let value = controller.state().read_byte(address)? as u32;
+        controller.mut_state().set_register_value(self.dest, value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for LoadByteInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LDB {}, {}, {}", self.dest, self.base, self.offset)
+    }
+}
+
+/// # LoadWordInstruction
+/// Loads a word from the address [base + offset]
+/// to [dest] register, the word counterpart of [LoadByteInstruction].
+/// [offset] is parsed as i8, the address has to be aligned.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [dest] register address
+/// - 3rd byte: [base] register address
+/// - 4th byte: offset
+///
+pub struct LoadWordInstruction {
+    dest: Register,
+    base: Register,
+    offset: i8,
+}
+
+impl LoadWordInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(LoadWordInstruction {
+            dest: Register::from_addr(code[1] as u32)?,
+            base: Register::from_addr(code[2] as u32)?,
+            offset: code[3] as i8,
+        })
+    }
+}
+
+impl Instruction for LoadWordInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let address = controller.state().register_value(self.base);
+        let address = address.wrapping_add_signed(self.offset as i32);
+        let value = controller.state().read_word(address)?;
+        controller.mut_state().set_register_value(self.dest, value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for LoadWordInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LDW {}, {}, {}", self.dest, self.base, self.offset)
+    }
+}
+
+/// # DerefInstruction
+/// Treats the value of [source] register as a pointer
+/// and loads the word from address [source + offset]
+/// to [dest] register.
+/// [offset] is parsed as i8, the address has to be aligned.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [dest] register address
+/// - 3rd byte: [source] register address to dereference
+/// - 4th byte: offset
+///
 pub struct DerefInstruction {
     dest: Register,
     source: Register,
@@ -865,20 +1275,2593 @@ pub struct DerefInstruction {
 }
 
 impl DerefInstruction {
-    pub fn new(code: &[u8]) -> Self {
-        DerefInstruction {
-            dest: Register::from_addr(code[1] as u32),
-            source: Register::from_addr(code[2] as u32),
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(DerefInstruction {
+            dest: Register::from_addr(code[1] as u32)?,
+            source: Register::from_addr(code[2] as u32)?,
             offset: code[3] as i8,
-        }
+        })
     }
 }
 
 impl Instruction for DerefInstruction {
-    fn execute(&mut self, controller: &mut Controller) {
-        let addr = controller.state().register_value(self.source) as i32;
-        let addr = addr + self.offset as i32;
-        let value = controller.state().get_memory_handler().read_word(addr as u32).to_owned();
-        controller.mut_state().get_mut_memory_handler().write_word(self.dest.as_addr(), &value);
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let address = controller.state().register_value(self.source);
+        let address = address.wrapping_add_signed(self.offset as i32);
+        let value = controller.state().read_word(address)?;
+        controller.mut_state().set_register_value(self.dest, value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for DerefInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DEREF {}, {}, {}", self.dest, self.source, self.offset)
+    }
+}
+
+/// # AndInstruction
+/// Bitwise AND of two numbers in given registers
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [first_register] address
+/// - 3rd byte: [second_register] address
+/// - 4th byte: [third_register] address
+///
+/// Result is stored in the [third_register]
+///
+pub struct AndInstruction {
+    first_register: Register,
+    second_register: Register,
+    third_register: Register,
+}
+
+impl AndInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(AndInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
+    }
+}
+
+impl Instruction for AndInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.first_register);
+        let second_value = state.register_value(self.second_register);
+        let result = first_value & second_value;
+        state.set_register_value(self.third_register, result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for AndInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "AND {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
+    }
+}
+
+/// # OrInstruction
+/// Bitwise OR of two numbers in given registers
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [first_register] address
+/// - 3rd byte: [second_register] address
+/// - 4th byte: [third_register] address
+///
+/// Result is stored in the [third_register]
+///
+pub struct OrInstruction {
+    first_register: Register,
+    second_register: Register,
+    third_register: Register,
+}
+
+impl OrInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(OrInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
+    }
+}
+
+impl Instruction for OrInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.first_register);
+        let second_value = state.register_value(self.second_register);
+        let result = first_value | second_value;
+        state.set_register_value(self.third_register, result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for OrInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "OR {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
     }
-}
\ No newline at end of file
+}
+
+/// # XorInstruction
+/// Bitwise XOR of two numbers in given registers
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [first_register] address
+/// - 3rd byte: [second_register] address
+/// - 4th byte: [third_register] address
+///
+/// Result is stored in the [third_register]
+///
+pub struct XorInstruction {
+    first_register: Register,
+    second_register: Register,
+    third_register: Register,
+}
+
+impl XorInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(XorInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
+    }
+}
+
+impl Instruction for XorInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.first_register);
+        let second_value = state.register_value(self.second_register);
+        let result = first_value ^ second_value;
+        state.set_register_value(self.third_register, result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for XorInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "XOR {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
+    }
+}
+
+/// # NotInstruction
+/// Inverts all bits of the value in [first_register]
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [first_register] address
+/// - 3rd byte: [second_register] address
+/// - 4th byte: not used
+///
+/// Result is stored in the [second_register]
+///
+pub struct NotInstruction {
+    first_register: Register,
+    second_register: Register,
+}
+
+impl NotInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(NotInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+        })
+    }
+}
+
+impl Instruction for NotInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let value = state.register_value(self.first_register);
+        state.set_register_value(self.second_register, !value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for NotInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "NOT {}, {}", self.first_register, self.second_register)
+    }
+}
+
+/// # ShiftLeftInstruction
+/// Shifts the value in [first_register] left
+/// by the number of bits in [second_register].
+/// Shifting by 32 bits or more gives 0.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [first_register] address
+/// - 3rd byte: [second_register] address
+/// - 4th byte: [third_register] address
+///
+/// Result is stored in the [third_register]
+///
+pub struct ShiftLeftInstruction {
+    first_register: Register,
+    second_register: Register,
+    third_register: Register,
+}
+
+impl ShiftLeftInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(ShiftLeftInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
+    }
+}
+
+impl Instruction for ShiftLeftInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.first_register);
+        let second_value = state.register_value(self.second_register);
+        let result = first_value.checked_shl(second_value).unwrap_or(0);
+        state.set_register_value(self.third_register, result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for ShiftLeftInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SHL {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
+    }
+}
+
+/// # ShiftRightInstruction
+/// Logically shifts the value in [first_register] right
+/// by the number of bits in [second_register], filling with zeros.
+/// Shifting by 32 bits or more gives 0.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [first_register] address
+/// - 3rd byte: [second_register] address
+/// - 4th byte: [third_register] address
+///
+/// Result is stored in the [third_register]
+///
+pub struct ShiftRightInstruction {
+    first_register: Register,
+    second_register: Register,
+    third_register: Register,
+}
+
+impl ShiftRightInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(ShiftRightInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
+    }
+}
+
+impl Instruction for ShiftRightInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.first_register);
+        let second_value = state.register_value(self.second_register);
+        let result = first_value.checked_shr(second_value).unwrap_or(0);
+        state.set_register_value(self.third_register, result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for ShiftRightInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SHR {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
+    }
+}
+
+/// # ShiftArithmeticRightInstruction
+/// Arithmetically shifts the value in [first_register] right
+/// by the number of bits in [second_register], filling with the sign bit.
+/// Shifting by 32 bits or more fills all the bits with the sign bit.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [first_register] address
+/// - 3rd byte: [second_register] address
+/// - 4th byte: [third_register] address
+///
+/// Result is stored in the [third_register]
+///
+pub struct ShiftArithmeticRightInstruction {
+    first_register: Register,
+    second_register: Register,
+    third_register: Register,
+}
+
+impl ShiftArithmeticRightInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(ShiftArithmeticRightInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
+    }
+}
+
+impl Instruction for ShiftArithmeticRightInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.first_register);
+        let second_value = state.register_value(self.second_register);
+        let first_value = first_value as i32;
+        let result = first_value
+            .checked_shr(second_value)
+            .unwrap_or(first_value >> 31) as u32;
+        state.set_register_value(self.third_register, result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for ShiftArithmeticRightInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "SAR {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
+    }
+}
+
+/// # SignedDivInstruction
+/// Divides two *signed integer* numbers in given registers
+/// The quotient is rounded towards zero.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [first_register] address
+/// - 3rd byte: [second_register] address
+/// - 4th byte: [third_register] address
+///
+/// Result is stored in the [third_register]
+///
+pub struct SignedDivInstruction {
+    first_register: Register,
+    second_register: Register,
+    third_register: Register,
+}
+
+impl SignedDivInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(SignedDivInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
+    }
+}
+
+impl Instruction for SignedDivInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.first_register);
+        let second_value = state.register_value(self.second_register);
+        let (first_value, second_value) = (first_value as i32, second_value as i32);
+        if second_value == 0 {
+            return Err(Fault::DivideByZero);
+        }
+        let result = first_value.wrapping_div(second_value) as u32;
+        state.set_register_value(self.third_register, result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for SignedDivInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "IDIV {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
+    }
+}
+
+/// # SignedModInstruction
+/// Finds the remainder of division of two *signed integer* numbers
+/// in given registers. The remainder has the sign of the dividend.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [first_register] address
+/// - 3rd byte: [second_register] address
+/// - 4th byte: [third_register] address
+///
+/// Result is stored in the [third_register]
+///
+pub struct SignedModInstruction {
+    first_register: Register,
+    second_register: Register,
+    third_register: Register,
+}
+
+impl SignedModInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(SignedModInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
+    }
+}
+
+impl Instruction for SignedModInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.first_register);
+        let second_value = state.register_value(self.second_register);
+        let (first_value, second_value) = (first_value as i32, second_value as i32);
+        if second_value == 0 {
+            return Err(Fault::DivideByZero);
+        }
+        let result = first_value.wrapping_rem(second_value) as u32;
+        state.set_register_value(self.third_register, result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for SignedModInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "IMOD {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
+    }
+}
+
+/// # SignedLessInstruction
+/// Compares values in [left] and [right] registers
+/// as signed numbers and stores 1 to CMP if left < right.
+/// Otherwise, stores 0.
+/// FLAGS are set as if right was subtracted from left.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [left] register address
+/// - 3rd byte: [right] register address
+/// - 4th byte: not used
+///
+pub struct SignedLessInstruction {
+    left: Register,
+    right: Register,
+}
+
+impl SignedLessInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(SignedLessInstruction {
+            left: Register::from_addr(code[1] as u32)?,
+            right: Register::from_addr(code[2] as u32)?,
+        })
+    }
+}
+
+impl Instruction for SignedLessInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let left_value = controller.state().register_value(self.left);
+        let right_value = controller.state().register_value(self.right);
+        set_compare_flags(controller.mut_state(), left_value, right_value);
+        let (left_value, right_value) = (left_value as i32, right_value as i32);
+        controller
+            .mut_state()
+            .set_register_value(Register::CMP, (left_value < right_value) as u32);
+        Ok(())
+    }
+}
+
+impl fmt::Display for SignedLessInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ILT {}, {}", self.left, self.right)
+    }
+}
+
+/// # SignedLessEqualInstruction
+/// Compares values in [left] and [right] registers
+/// as signed numbers and stores 1 to CMP if left <= right.
+/// Otherwise, stores 0.
+/// FLAGS are set as if right was subtracted from left.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [left] register address
+/// - 3rd byte: [right] register address
+/// - 4th byte: not used
+///
+pub struct SignedLessEqualInstruction {
+    left: Register,
+    right: Register,
+}
+
+impl SignedLessEqualInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(SignedLessEqualInstruction {
+            left: Register::from_addr(code[1] as u32)?,
+            right: Register::from_addr(code[2] as u32)?,
+        })
+    }
+}
+
+impl Instruction for SignedLessEqualInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let left_value = controller.state().register_value(self.left);
+        let right_value = controller.state().register_value(self.right);
+        set_compare_flags(controller.mut_state(), left_value, right_value);
+        let (left_value, right_value) = (left_value as i32, right_value as i32);
+        controller
+            .mut_state()
+            .set_register_value(Register::CMP, (left_value <= right_value) as u32);
+        Ok(())
+    }
+}
+
+impl fmt::Display for SignedLessEqualInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ILE {}, {}", self.left, self.right)
+    }
+}
+
+/// # SignedGreaterInstruction
+/// Compares values in [left] and [right] registers
+/// as signed numbers and stores 1 to CMP if left > right.
+/// Otherwise, stores 0.
+/// FLAGS are set as if right was subtracted from left.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [left] register address
+/// - 3rd byte: [right] register address
+/// - 4th byte: not used
+///
+pub struct SignedGreaterInstruction {
+    left: Register,
+    right: Register,
+}
+
+impl SignedGreaterInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(SignedGreaterInstruction {
+            left: Register::from_addr(code[1] as u32)?,
+            right: Register::from_addr(code[2] as u32)?,
+        })
+    }
+}
+
+impl Instruction for SignedGreaterInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let left_value = controller.state().register_value(self.left);
+        let right_value = controller.state().register_value(self.right);
+        set_compare_flags(controller.mut_state(), left_value, right_value);
+        let (left_value, right_value) = (left_value as i32, right_value as i32);
+        controller
+            .mut_state()
+            .set_register_value(Register::CMP, (left_value > right_value) as u32);
+        Ok(())
+    }
+}
+
+impl fmt::Display for SignedGreaterInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IGT {}, {}", self.left, self.right)
+    }
+}
+
+/// # SignedGreaterEqualInstruction
+/// Compares values in [left] and [right] registers
+/// as signed numbers and stores 1 to CMP if left >= right.
+/// Otherwise, stores 0.
+/// FLAGS are set as if right was subtracted from left.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [left] register address
+/// - 3rd byte: [right] register address
+/// - 4th byte: not used
+///
+pub struct SignedGreaterEqualInstruction {
+    left: Register,
+    right: Register,
+}
+
+impl SignedGreaterEqualInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(SignedGreaterEqualInstruction {
+            left: Register::from_addr(code[1] as u32)?,
+            right: Register::from_addr(code[2] as u32)?,
+        })
+    }
+}
+
+impl Instruction for SignedGreaterEqualInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let left_value = controller.state().register_value(self.left);
+        let right_value = controller.state().register_value(self.right);
+        set_compare_flags(controller.mut_state(), left_value, right_value);
+        let (left_value, right_value) = (left_value as i32, right_value as i32);
+        controller
+            .mut_state()
+            .set_register_value(Register::CMP, (left_value >= right_value) as u32);
+        Ok(())
+    }
+}
+
+impl fmt::Display for SignedGreaterEqualInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IGE {}, {}", self.left, self.right)
+    }
+}
+
+/// # OutSignedNumberInstruction
+/// Outputs a number stored in the [register]
+/// as a signed number
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: not used
+/// - 4th byte: not used
+pub struct OutSignedNumberInstruction {
+    register: Register,
+}
+
+impl OutSignedNumberInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(OutSignedNumberInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+        })
+    }
+}
+
+impl Instruction for OutSignedNumberInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let number = controller.state().register_value(self.register) as i32;
+        let number_str = number.to_string();
+        for c in number_str.chars() {
+            controller.print(c)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for OutSignedNumberInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OUTNI {}", self.register)
+    }
+}
+
+/// InputSignedNumberInstruction
+/// Gets a signed number from input and puts it
+/// into the [register] in two's complement
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: not used
+/// - 4th byte: not used
+///
+pub struct InputSignedNumberInstruction {
+    register: Register,
+}
+
+impl InputSignedNumberInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(InputSignedNumberInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+        })
+    }
+}
+
+impl Instruction for InputSignedNumberInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let num = controller.mut_display().get_signed_num()?;
+        controller
+            .mut_state()
+            .set_register_value(self.register, num as u32);
+        Ok(())
+    }
+}
+
+impl fmt::Display for InputSignedNumberInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "INPNI {}", self.register)
+    }
+}
+
+/// # JumpZeroInstruction
+/// Moves [IP] register by the [offset] if Zero flag
+/// is set [offset] is parsed as little endian i16
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: offset
+/// - 3rd byte: offset
+/// - 4th byte: not used
+///
+pub struct JumpZeroInstruction {
+    offset: i16,
+    success: bool,
+}
+
+impl JumpZeroInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpZeroInstruction {
+            offset: LittleEndian::read_i16(&code[1..=2]),
+            success: true,
+        })
+    }
+}
+
+impl Instruction for JumpZeroInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        if !controller.state().flag(Flag::Zero) {
+            self.success = false;
+            return Ok(());
+        }
+        controller.jump(self.offset);
+        Ok(())
+    }
+
+    fn move_ip(&self) -> bool {
+        !self.success
+    }
+}
+
+impl fmt::Display for JumpZeroInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JZ {}", self.offset)
+    }
+}
+
+/// # JumpNotZeroInstruction
+/// Moves [IP] register by the [offset] if Zero flag
+/// is not set [offset] is parsed as little endian i16
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: offset
+/// - 3rd byte: offset
+/// - 4th byte: not used
+///
+pub struct JumpNotZeroInstruction {
+    offset: i16,
+    success: bool,
+}
+
+impl JumpNotZeroInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpNotZeroInstruction {
+            offset: LittleEndian::read_i16(&code[1..=2]),
+            success: true,
+        })
+    }
+}
+
+impl Instruction for JumpNotZeroInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        if controller.state().flag(Flag::Zero) {
+            self.success = false;
+            return Ok(());
+        }
+        controller.jump(self.offset);
+        Ok(())
+    }
+
+    fn move_ip(&self) -> bool {
+        !self.success
+    }
+}
+
+impl fmt::Display for JumpNotZeroInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JNZ {}", self.offset)
+    }
+}
+
+/// # JumpCarryInstruction
+/// Moves [IP] register by the [offset] if Carry flag
+/// is set [offset] is parsed as little endian i16
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: offset
+/// - 3rd byte: offset
+/// - 4th byte: not used
+///
+pub struct JumpCarryInstruction {
+    offset: i16,
+    success: bool,
+}
+
+impl JumpCarryInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpCarryInstruction {
+            offset: LittleEndian::read_i16(&code[1..=2]),
+            success: true,
+        })
+    }
+}
+
+impl Instruction for JumpCarryInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        if !controller.state().flag(Flag::Carry) {
+            self.success = false;
+            return Ok(());
+        }
+        controller.jump(self.offset);
+        Ok(())
+    }
+
+    fn move_ip(&self) -> bool {
+        !self.success
+    }
+}
+
+impl fmt::Display for JumpCarryInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JC {}", self.offset)
+    }
+}
+
+/// # JumpNotCarryInstruction
+/// Moves [IP] register by the [offset] if Carry flag
+/// is not set [offset] is parsed as little endian i16
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: offset
+/// - 3rd byte: offset
+/// - 4th byte: not used
+///
+pub struct JumpNotCarryInstruction {
+    offset: i16,
+    success: bool,
+}
+
+impl JumpNotCarryInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpNotCarryInstruction {
+            offset: LittleEndian::read_i16(&code[1..=2]),
+            success: true,
+        })
+    }
+}
+
+impl Instruction for JumpNotCarryInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        if controller.state().flag(Flag::Carry) {
+            self.success = false;
+            return Ok(());
+        }
+        controller.jump(self.offset);
+        Ok(())
+    }
+
+    fn move_ip(&self) -> bool {
+        !self.success
+    }
+}
+
+impl fmt::Display for JumpNotCarryInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JNC {}", self.offset)
+    }
+}
+
+/// # JumpOverflowInstruction
+/// Moves [IP] register by the [offset] if Overflow flag
+/// is set [offset] is parsed as little endian i16
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: offset
+/// - 3rd byte: offset
+/// - 4th byte: not used
+///
+pub struct JumpOverflowInstruction {
+    offset: i16,
+    success: bool,
+}
+
+impl JumpOverflowInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpOverflowInstruction {
+            offset: LittleEndian::read_i16(&code[1..=2]),
+            success: true,
+        })
+    }
+}
+
+impl Instruction for JumpOverflowInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        if !controller.state().flag(Flag::Overflow) {
+            self.success = false;
+            return Ok(());
+        }
+        controller.jump(self.offset);
+        Ok(())
+    }
+
+    fn move_ip(&self) -> bool {
+        !self.success
+    }
+}
+
+impl fmt::Display for JumpOverflowInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JO {}", self.offset)
+    }
+}
+
+/// # JumpNotOverflowInstruction
+/// Moves [IP] register by the [offset] if Overflow flag
+/// is not set [offset] is parsed as little endian i16
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: offset
+/// - 3rd byte: offset
+/// - 4th byte: not used
+///
+pub struct JumpNotOverflowInstruction {
+    offset: i16,
+    success: bool,
+}
+
+impl JumpNotOverflowInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpNotOverflowInstruction {
+            offset: LittleEndian::read_i16(&code[1..=2]),
+            success: true,
+        })
+    }
+}
+
+impl Instruction for JumpNotOverflowInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        if controller.state().flag(Flag::Overflow) {
+            self.success = false;
+            return Ok(());
+        }
+        controller.jump(self.offset);
+        Ok(())
+    }
+
+    fn move_ip(&self) -> bool {
+        !self.success
+    }
+}
+
+impl fmt::Display for JumpNotOverflowInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JNO {}", self.offset)
+    }
+}
+
+/// # JumpSignInstruction
+/// Moves [IP] register by the [offset] if Sign flag
+/// is set [offset] is parsed as little endian i16
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: offset
+/// - 3rd byte: offset
+/// - 4th byte: not used
+///
+pub struct JumpSignInstruction {
+    offset: i16,
+    success: bool,
+}
+
+impl JumpSignInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpSignInstruction {
+            offset: LittleEndian::read_i16(&code[1..=2]),
+            success: true,
+        })
+    }
+}
+
+impl Instruction for JumpSignInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        if !controller.state().flag(Flag::Sign) {
+            self.success = false;
+            return Ok(());
+        }
+        controller.jump(self.offset);
+        Ok(())
+    }
+
+    fn move_ip(&self) -> bool {
+        !self.success
+    }
+}
+
+impl fmt::Display for JumpSignInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JS {}", self.offset)
+    }
+}
+
+/// # JumpNotSignInstruction
+/// Moves [IP] register by the [offset] if Sign flag
+/// is not set [offset] is parsed as little endian i16
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: offset
+/// - 3rd byte: offset
+/// - 4th byte: not used
+///
+pub struct JumpNotSignInstruction {
+    offset: i16,
+    success: bool,
+}
+
+impl JumpNotSignInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpNotSignInstruction {
+            offset: LittleEndian::read_i16(&code[1..=2]),
+            success: true,
+        })
+    }
+}
+
+impl Instruction for JumpNotSignInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        if controller.state().flag(Flag::Sign) {
+            self.success = false;
+            return Ok(());
+        }
+        controller.jump(self.offset);
+        Ok(())
+    }
+
+    fn move_ip(&self) -> bool {
+        !self.success
+    }
+}
+
+impl fmt::Display for JumpNotSignInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JNS {}", self.offset)
+    }
+}
+
+/// # ModInstruction
+/// Finds the remainder of division of two *unsigned integer* numbers
+/// in given registers
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [first_register] address
+/// - 3rd byte: [second_register] address
+/// - 4th byte: [third_register] address
+///
+/// Result is stored in the [third_register]
+///
+pub struct ModInstruction {
+    first_register: Register,
+    second_register: Register,
+    third_register: Register,
+}
+
+impl ModInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(ModInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
+    }
+}
+
+impl Instruction for ModInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.first_register);
+        let second_value = state.register_value(self.second_register);
+        if second_value == 0 {
+            return Err(Fault::DivideByZero);
+        }
+        let result = first_value % second_value;
+        state.set_register_value(self.third_register, result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for ModInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MOD {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
+    }
+}
+
+/// # MulHighInstruction
+/// Multiplies two *unsigned* numbers in given registers
+/// and keeps the high 32 bits of the 64-bit product.
+/// `MUL` with the same operands gives the low 32 bits.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [first_register] address
+/// - 3rd byte: [second_register] address
+/// - 4th byte: [third_register] address
+///
+/// Result is stored in the [third_register]
+///
+pub struct MulHighInstruction {
+    first_register: Register,
+    second_register: Register,
+    third_register: Register,
+}
+
+impl MulHighInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(MulHighInstruction {
+            first_register: Register::from_addr(code[1] as u32)?,
+            second_register: Register::from_addr(code[2] as u32)?,
+            third_register: Register::from_addr(code[3] as u32)?,
+        })
+    }
+}
+
+impl Instruction for MulHighInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.first_register);
+        let second_value = state.register_value(self.second_register);
+        let product = first_value as u64 * second_value as u64;
+        let result = (product >> 32) as u32;
+        state.set_register_value(self.third_register, result);
+        Ok(())
+    }
+}
+
+impl fmt::Display for MulHighInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "MULH {}, {}, {}",
+            self.first_register, self.second_register, self.third_register
+        )
+    }
+}
+
+/// # AddImmediateInstruction
+/// Adds [value] to the number in [register],
+/// the result is stored back to [register] and FLAGS are updated.
+/// [value] is parsed as little-endian i16.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: value
+/// - 4th byte: value
+///
+pub struct AddImmediateInstruction {
+    register: Register,
+    value: i16,
+}
+
+impl AddImmediateInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(AddImmediateInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+            value: LittleEndian::read_i16(&code[2..=3]),
+        })
+    }
+}
+
+impl Instruction for AddImmediateInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.register);
+        let second_value = self.value as i32 as u32;
+        let (result, carry) = first_value.overflowing_add(second_value);
+        let (_, overflow) = (first_value as i32).overflowing_add(second_value as i32);
+        state.set_register_value(self.register, result);
+        state.set_flags(result, carry, overflow);
+        Ok(())
+    }
+}
+
+impl fmt::Display for AddImmediateInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ADDI {}, {}", self.register, self.value)
+    }
+}
+
+/// # SubImmediateInstruction
+/// Subtracts [value] from the number in [register],
+/// the result is stored back to [register] and FLAGS are updated.
+/// [value] is parsed as little-endian i16.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: value
+/// - 4th byte: value
+///
+pub struct SubImmediateInstruction {
+    register: Register,
+    value: i16,
+}
+
+impl SubImmediateInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(SubImmediateInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+            value: LittleEndian::read_i16(&code[2..=3]),
+        })
+    }
+}
+
+impl Instruction for SubImmediateInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.register);
+        let second_value = self.value as i32 as u32;
+        let (result, carry) = first_value.overflowing_sub(second_value);
+        let (_, overflow) = (first_value as i32).overflowing_sub(second_value as i32);
+        state.set_register_value(self.register, result);
+        state.set_flags(result, carry, overflow);
+        Ok(())
+    }
+}
+
+impl fmt::Display for SubImmediateInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SUBI {}, {}", self.register, self.value)
+    }
+}
+
+/// # CompareImmediateInstruction
+/// Compares the number in [register] with [value] and stores 1
+/// to CMP if they are equal. Otherwise, stores 0.
+/// FLAGS are set as if value was subtracted from the register.
+/// [value] is parsed as little-endian i16.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: value
+/// - 4th byte: value
+///
+pub struct CompareImmediateInstruction {
+    register: Register,
+    value: i16,
+}
+
+impl CompareImmediateInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(CompareImmediateInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+            value: LittleEndian::read_i16(&code[2..=3]),
+        })
+    }
+}
+
+impl Instruction for CompareImmediateInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let first_value = state.register_value(self.register);
+        let second_value = self.value as i32 as u32;
+        state.set_register_value(Register::CMP, (first_value == second_value) as u32);
+        set_compare_flags(state, first_value, second_value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for CompareImmediateInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CMPI {}, {}", self.register, self.value)
+    }
+}
+
+/// # LoadUpperImmediateInstruction
+/// Loads [value] to the upper 16 bits of [register]
+/// and clears the lower 16 bits.
+/// [value] is parsed as little-endian u16.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: value
+/// - 4th byte: value
+///
+pub struct LoadUpperImmediateInstruction {
+    register: Register,
+    value: u16,
+}
+
+impl LoadUpperImmediateInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(LoadUpperImmediateInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+            value: LittleEndian::read_u16(&code[2..=3]),
+        })
+    }
+}
+
+impl Instruction for LoadUpperImmediateInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        controller
+            .mut_state()
+            .set_register_value(self.register, (self.value as u32) << 16);
+        Ok(())
+    }
+}
+
+impl fmt::Display for LoadUpperImmediateInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LUI {}, {:#x}", self.register, self.value)
+    }
+}
+
+/// # LoadLowerImmediateInstruction
+/// Loads [value] to the lower 16 bits of [register]
+/// and keeps the upper 16 bits, so `LUI` followed by
+/// `LLI` loads a full 32-bit constant.
+/// [value] is parsed as little-endian u16.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: value
+/// - 4th byte: value
+///
+pub struct LoadLowerImmediateInstruction {
+    register: Register,
+    value: u16,
+}
+
+impl LoadLowerImmediateInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(LoadLowerImmediateInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+            value: LittleEndian::read_u16(&code[2..=3]),
+        })
+    }
+}
+
+impl Instruction for LoadLowerImmediateInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let upper = state.register_value(self.register) & 0xFFFF_0000;
+        state.set_register_value(self.register, upper | self.value as u32);
+        Ok(())
+    }
+}
+
+impl fmt::Display for LoadLowerImmediateInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LLI {}, {:#x}", self.register, self.value)
+    }
+}
+
+/// # JumpRegisterInstruction
+/// Moves [IP] register to the address held in [register]
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: not used
+/// - 4th byte: not used
+///
+pub struct JumpRegisterInstruction {
+    register: Register,
+}
+
+impl JumpRegisterInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(JumpRegisterInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+        })
+    }
+}
+
+impl Instruction for JumpRegisterInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let address = controller.state().register_value(self.register);
+        controller.jump_abs(address);
+        Ok(())
+    }
+
+    fn move_ip(&self) -> bool {
+        false
+    }
+}
+
+impl fmt::Display for JumpRegisterInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "JMPR {}", self.register)
+    }
+}
+
+/// CallRegisterInstruction
+/// Calls an instruction on the address held in [register],
+/// returns with [RetInstruction] like [CallInstruction]
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: not used
+/// - 4th byte: not used
+pub struct CallRegisterInstruction {
+    register: Register,
+}
+
+impl CallRegisterInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(CallRegisterInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+        })
+    }
+}
+
+impl Instruction for CallRegisterInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let address = controller.state().register_value(self.register);
+        controller.mut_state().push_to_stack(Register::IP)?;
+        controller.jump_abs(address);
+        Ok(())
+    }
+
+    fn move_ip(&self) -> bool {
+        false
+    }
+}
+
+impl fmt::Display for CallRegisterInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CALLR {}", self.register)
+    }
+}
+
+/// # LoadStackInstruction
+/// Loads a word from address [SP + offset] to [register].
+/// [offset] is parsed as little-endian i16.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: offset
+/// - 4th byte: offset
+///
+pub struct LoadStackInstruction {
+    register: Register,
+    offset: i16,
+}
+
+impl LoadStackInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(LoadStackInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+            offset: LittleEndian::read_i16(&code[2..=3]),
+        })
+    }
+}
+
+impl Instruction for LoadStackInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let address = controller
+            .state()
+            .register_value(Register::SP)
+            .wrapping_add_signed(self.offset as i32);
+        let value = controller.state().read_word(address)?;
+        controller
+            .mut_state()
+            .set_register_value(self.register, value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for LoadStackInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LDS {}, {}", self.register, self.offset)
+    }
+}
+
+/// # StoreStackInstruction
+/// Stores the value of [register] to address [SP + offset].
+/// [offset] is parsed as little-endian i16.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: offset
+/// - 4th byte: offset
+///
+pub struct StoreStackInstruction {
+    register: Register,
+    offset: i16,
+}
+
+impl StoreStackInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(StoreStackInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+            offset: LittleEndian::read_i16(&code[2..=3]),
+        })
+    }
+}
+
+impl Instruction for StoreStackInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let address = controller
+            .state()
+            .register_value(Register::SP)
+            .wrapping_add_signed(self.offset as i32);
+        let value = controller.state().register_value(self.register);
+        controller.mut_state().write_word(address, value)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for StoreStackInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "STS {}, {}", self.register, self.offset)
+    }
+}
+
+/// # LoadFrameInstruction
+/// Loads a word from address [FP + offset] to [register].
+/// [offset] is parsed as little-endian i16.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: offset
+/// - 4th byte: offset
+///
+pub struct LoadFrameInstruction {
+    register: Register,
+    offset: i16,
+}
+
+impl LoadFrameInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(LoadFrameInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+            offset: LittleEndian::read_i16(&code[2..=3]),
+        })
+    }
+}
+
+impl Instruction for LoadFrameInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let address = controller
+            .state()
+            .register_value(Register::FP)
+            .wrapping_add_signed(self.offset as i32);
+        let value = controller.state().read_word(address)?;
+        controller
+            .mut_state()
+            .set_register_value(self.register, value);
+        Ok(())
+    }
+}
+
+impl fmt::Display for LoadFrameInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LDF {}, {}", self.register, self.offset)
+    }
+}
+
+/// # StoreFrameInstruction
+/// Stores the value of [register] to address [FP + offset].
+/// [offset] is parsed as little-endian i16.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: [register] address
+/// - 3rd byte: offset
+/// - 4th byte: offset
+///
+pub struct StoreFrameInstruction {
+    register: Register,
+    offset: i16,
+}
+
+impl StoreFrameInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(StoreFrameInstruction {
+            register: Register::from_addr(code[1] as u32)?,
+            offset: LittleEndian::read_i16(&code[2..=3]),
+        })
+    }
+}
+
+impl Instruction for StoreFrameInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let address = controller
+            .state()
+            .register_value(Register::FP)
+            .wrapping_add_signed(self.offset as i32);
+        let value = controller.state().register_value(self.register);
+        controller.mut_state().write_word(address, value)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for StoreFrameInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "STF {}, {}", self.register, self.offset)
+    }
+}
+
+/// # EnterInstruction
+/// Creates a stack frame: pushes FP to the stack,
+/// points FP to the top of the stack and reserves
+/// [size] bytes for local variables above it.
+/// [size] is parsed as little-endian u16.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: size
+/// - 3rd byte: size
+/// - 4th byte: not used
+///
+pub struct EnterInstruction {
+    size: u16,
+}
+
+impl EnterInstruction {
+    pub fn new(code: &[u8]) -> Result<Self, Fault> {
+        Ok(EnterInstruction {
+            size: LittleEndian::read_u16(&code[1..=2]),
+        })
+    }
+}
+
+impl Instruction for EnterInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        state.push_to_stack(Register::FP)?;
+        let sp_value = state.register_value(Register::SP);
+        state.set_register_value(Register::FP, sp_value);
+        state.reserve_stack(self.size as u32)
+    }
+}
+
+impl fmt::Display for EnterInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ENTER {}", self.size)
+    }
+}
+
+/// # LeaveInstruction
+/// Destroys the stack frame created by [EnterInstruction]:
+/// moves SP back to FP and pops the previous FP.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: not used
+/// - 3rd byte: not used
+/// - 4th byte: not used
+///
+pub struct LeaveInstruction {}
+
+impl LeaveInstruction {
+    pub fn new(_code: &[u8]) -> Result<Self, Fault> {
+        Ok(LeaveInstruction {})
+    }
+}
+
+impl Instruction for LeaveInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        let fp_value = state.register_value(Register::FP);
+        state.set_register_value(Register::SP, fp_value);
+        state.pop_from_stack(Register::FP)
+    }
+}
+
+impl fmt::Display for LeaveInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LEAVE")
+    }
+}
+
+/// # EnableInterruptsInstruction
+/// Sets the Interrupt flag, so the pending interrupts
+/// are taken before the next instructions.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: not used
+/// - 3rd byte: not used
+/// - 4th byte: not used
+///
+pub struct EnableInterruptsInstruction {}
+
+impl EnableInterruptsInstruction {
+    pub fn new(_code: &[u8]) -> Result<Self, Fault> {
+        Ok(EnableInterruptsInstruction {})
+    }
+}
+
+impl Instruction for EnableInterruptsInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        controller.mut_state().set_flag(Flag::Interrupt, true);
+        Ok(())
+    }
+}
+
+impl fmt::Display for EnableInterruptsInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EI")
+    }
+}
+
+/// # DisableInterruptsInstruction
+/// Clears the Interrupt flag. The interrupts raised
+/// meanwhile stay pending until the flag is set again.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: not used
+/// - 3rd byte: not used
+/// - 4th byte: not used
+///
+pub struct DisableInterruptsInstruction {}
+
+impl DisableInterruptsInstruction {
+    pub fn new(_code: &[u8]) -> Result<Self, Fault> {
+        Ok(DisableInterruptsInstruction {})
+    }
+}
+
+impl Instruction for DisableInterruptsInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        controller.mut_state().set_flag(Flag::Interrupt, false);
+        Ok(())
+    }
+}
+
+impl fmt::Display for DisableInterruptsInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DI")
+    }
+}
+
+/// # InterruptReturnInstruction
+/// Returns from an interrupt handler: pops IP and FLAGS
+/// pushed when the interrupt was taken. The interrupted
+/// instruction has not been executed yet, so IP is not moved.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: not used
+/// - 3rd byte: not used
+/// - 4th byte: not used
+///
+pub struct InterruptReturnInstruction {}
+
+impl InterruptReturnInstruction {
+    pub fn new(_code: &[u8]) -> Result<Self, Fault> {
+        Ok(InterruptReturnInstruction {})
+    }
+}
+
+impl Instruction for InterruptReturnInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let state = controller.mut_state();
+        state.pop_from_stack(Register::IP)?;
+        state.pop_from_stack(Register::FLAGS)
+    }
+
+    fn move_ip(&self) -> bool {
+        false
+    }
+}
+
+impl fmt::Display for InterruptReturnInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IRET")
+    }
+}
+
+/// # SyscallInstruction
+/// Calls the host handler registered on the [Controller]
+/// for the number in R0, see [Controller::syscall].
+/// Arguments and the result are passed in the registers.
+///
+/// Structure:
+/// - 1st byte: instruction code
+/// - 2nd byte: not used
+/// - 3rd byte: not used
+/// - 4th byte: not used
+///
+pub struct SyscallInstruction {}
+
+impl SyscallInstruction {
+    pub fn new(_code: &[u8]) -> Result<Self, Fault> {
+        Ok(SyscallInstruction {})
+    }
+}
+
+impl Instruction for SyscallInstruction {
+    fn execute(&mut self, controller: &mut Controller) -> Result<(), Fault> {
+        let number = controller.state().register_value(Register::R0);
+        controller.syscall(number)
+    }
+}
+
+impl fmt::Display for SyscallInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SYSCALL")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode;
+    use crate::vm::arch::ARCH_BYTES;
+    use crate::vm::components::controller::Outcome;
+    use crate::vm::components::display::ScriptedDisplay;
+    use crate::vm::components::state::{Flag, StackBounds};
+    use crate::vm::error::Fault;
+    use crate::vm::tools::assembler::assemble;
+    use crate::{BufferMemory, Controller, Register, State, VmError};
+
+    /// Address of the [index]-th instruction of an assembled program.
+    fn code_address(index: u32) -> u32 {
+        Register::header_size() + index * ARCH_BYTES
+    }
+
+    fn run(source: &str) -> (Controller, Result<Outcome, VmError>) {
+        let image = assemble(source).unwrap();
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let mut controller = Controller::new(state);
+        let result = controller.execute();
+        (controller, result)
+    }
+
+    #[test]
+    fn rejects_short_words() {
+        assert!(decode(&[0x07, 0, 0, 0]).is_ok());
+        assert!(matches!(
+            decode(&[0x07, 0, 0]),
+            Err(Fault::InvalidOpcode(0x07))
+        ));
+        assert!(matches!(decode(&[]), Err(Fault::InvalidOpcode(0))));
+    }
+
+    #[test]
+    fn stores_and_loads_memory() {
+        let (controller, result) = run("
+                LDA R1, buffer
+                LDA R0, 0x1234
+                STW R0, R1
+                LDA R0, 0xAB
+                STB R0, R1, 5
+                LDB R2, R1, 1
+                LDW R3, R1
+                LDB R0, R1, 5
+                FIN
+            buffer: .zero 8
+            ");
+
+        assert_eq!(result, Ok(Outcome::Finished));
+        assert_eq!(controller.state().register_value(Register::R0), 0xAB);
+        assert_eq!(controller.state().register_value(Register::R2), 0x12);
+        assert_eq!(controller.state().register_value(Register::R3), 0x1234);
+    }
+
+    #[test]
+    fn loads_words_relative_to_a_register() {
+        let (controller, result) = run("
+                LDA R1, last
+                LDW R0, R1, -8
+                LDW R2, R1, -4
+                LDW R3, R1
+                FIN
+                .word 0x11, 0x22
+            last: .word 0x33
+            ");
+
+        assert_eq!(result, Ok(Outcome::Finished));
+        assert_eq!(controller.state().register_value(Register::R0), 0x11);
+        assert_eq!(controller.state().register_value(Register::R2), 0x22);
+        assert_eq!(controller.state().register_value(Register::R3), 0x33);
+    }
+
+    #[test]
+    fn dereferences_with_offsets() {
+        let (controller, result) = run("
+                LDA R0, node
+                DEREF R1, R0, 4
+                DEREF R2, R0, -4
+                DEREF R3, R0
+                FIN
+            first: .word 11
+            node: .word 22, first
+            ");
+
+        assert_eq!(result, Ok(Outcome::Finished));
+        let state = controller.state();
+        assert_eq!(
+            state.register_value(Register::R1),
+            state.register_value(Register::R0) - 4
+        );
+        assert_eq!(state.register_value(Register::R2), 11);
+        assert_eq!(state.register_value(Register::R3), 22);
+    }
+
+    #[test]
+    fn dereference_faults() {
+        let (_, result) = run("
+                LDA R0, 0x1000
+                DEREF R1, R0, -4
+                FIN
+            ");
+        assert_eq!(
+            result,
+            Err(VmError::OutOfBounds {
+                ip: code_address(1),
+                address: 0xFFC
+            })
+        );
+
+        let (_, result) = run("
+                LDA R0, 0x20
+                DEREF R1, R0, 1
+                FIN
+            ");
+        assert_eq!(
+            result,
+            Err(VmError::MisalignedAccess {
+                ip: code_address(1),
+                address: 0x21
+            })
+        );
+    }
+
+    fn registers_after(source: &str) -> [u32; 4] {
+        let (controller, result) = run(source);
+        assert_eq!(result, Ok(Outcome::Finished));
+        [Register::R0, Register::R1, Register::R2, Register::R3]
+            .map(|register| controller.state().register_value(register))
+    }
+
+    #[test]
+    fn bitwise_operations() {
+        let [r0, r1, r2, r3] = registers_after(
+            "
+                LDA R0, 0xF0F0
+                LDA R1, 0xFF00
+                AND R0, R1, R2
+                OR R0, R1, R3
+                XOR R0, R1, R0
+                NOT R1, R1
+                FIN
+            ",
+        );
+        assert_eq!(r0, 0x0FF0);
+        assert_eq!(r1, 0xFFFF00FF);
+        assert_eq!(r2, 0xF000);
+        assert_eq!(r3, 0xFFF0);
+    }
+
+    #[test]
+    fn shifts() {
+        let [r0, r1, r2, r3] = registers_after(
+            "
+                LDA R0, 1
+                LDA R1, 31
+                SHL R0, R1, R0
+                SAR R0, R1, R2
+                SHR R0, R1, R3
+                SHL R0, R1, R1
+                FIN
+            ",
+        );
+        assert_eq!(r0, 0x80000000);
+        assert_eq!(r1, 0);
+        assert_eq!(r2, 0xFFFFFFFF);
+        assert_eq!(r3, 1);
+    }
+
+    #[test]
+    fn shifts_by_32_or_more() {
+        let [r0, r1, r2, r3] = registers_after(
+            "
+                LDA R0, 0x8001
+                LDA R1, 0x10
+                SHL R0, R1, R0
+                LDA R1, 32
+                SAR R0, R1, R2
+                SHR R0, R1, R3
+                LDA R1, 200
+                SHL R0, R1, R1
+                FIN
+            ",
+        );
+        assert_eq!(r0, 0x80010000);
+        assert_eq!(r1, 0);
+        assert_eq!(r2, 0xFFFFFFFF);
+        assert_eq!(r3, 0);
+    }
+
+    #[test]
+    fn signed_division() {
+        let [r0, r1, r2, r3] = registers_after(
+            "
+                LDA R0, 7
+                LDA R1, 0
+                SUB R1, R0, R0      ; R0 = -7
+                LDA R1, 2
+                IDIV R0, R1, R2
+                IMOD R0, R1, R3
+                FIN
+            ",
+        );
+        assert_eq!(r0 as i32, -7);
+        assert_eq!(r1, 2);
+        assert_eq!(r2 as i32, -3);
+        assert_eq!(r3 as i32, -1);
+
+        let (_, result) = run("
+                LDA R0, 7
+                IMOD R0, R1, R2
+                FIN
+            ");
+        assert_eq!(
+            result,
+            Err(VmError::DivideByZero {
+                ip: code_address(1)
+            })
+        );
+    }
+
+    #[test]
+    fn signed_comparisons() {
+        let [r0, r1, r2, r3] = registers_after(
+            "
+                LDA R1, 1
+                SUB R0, R1, R1      ; R1 = -1
+                ILT R1, R0
+                MOV CMP, R2
+                L R1, R0
+                MOV CMP, R3
+                IGE R0, R1
+                MOV CMP, R0
+                FIN
+            ",
+        );
+        assert_eq!(r0, 1);
+        assert_eq!(r1 as i32, -1);
+        assert_eq!(r2, 1);
+        assert_eq!(r3, 0);
+    }
+
+    #[test]
+    fn remainder_and_wide_multiplication() {
+        let [r0, r1, r2, r3] = registers_after(
+            "
+                LDA R0, 47
+                LDA R1, 10
+                MOD R0, R1, R2
+                LDA R0, 0xFFFF
+                MUL R0, R0, R0      ; R0 = 0xFFFE0001
+                LDA R1, 0x100
+                MULH R0, R1, R3
+                MUL R0, R1, R1
+                FIN
+            ",
+        );
+        assert_eq!(r0, 0xFFFE0001);
+        assert_eq!(r1, 0xFE000100);
+        assert_eq!(r2, 7);
+        assert_eq!(r3, 0xFF);
+
+        let (_, result) = run("
+                LDA R0, 1
+                MOD R0, R1, R2
+                FIN
+            ");
+        assert_eq!(
+            result,
+            Err(VmError::DivideByZero {
+                ip: code_address(1)
+            })
+        );
+    }
+
+    #[test]
+    fn immediate_operands() {
+        let [r0, r1, _, _] = registers_after(
+            "
+                LDA R0, 10
+            loop:
+                ADDI R1, 4
+                SUBI R0, 1
+                JNZ loop
+                ADDI R0, -3
+                FIN
+            ",
+        );
+        assert_eq!(r0 as i32, -3);
+        assert_eq!(r1, 40);
+
+        let [_, _, r2, r3] = registers_after(
+            "
+                LDA R0, 5
+                CMPI R0, 5
+                MOV CMP, R2
+                CMPI R0, -1
+                JC below
+                LDA R3, 1
+            below:
+                FIN
+            ",
+        );
+        assert_eq!(r2, 1);
+        assert_eq!(r3, 0);
+    }
+
+    #[test]
+    fn loads_32_bit_constants() {
+        let [r0, r1, r2, _] = registers_after(
+            "
+                LDI R0, 0xDEADBEEF
+                LDI R1, -2
+                LDA R2, 0x1234
+                LUI R2, 0xABCD
+                FIN
+            ",
+        );
+        assert_eq!(r0, 0xDEADBEEF);
+        assert_eq!(r1 as i32, -2);
+        assert_eq!(r2, 0xABCD0000);
+    }
+
+    #[test]
+    fn indirect_jumps_and_calls() {
+        let [r0, r1, _, _] = registers_after(
+            "
+                LDA R3, handlers
+                DEREF R3, R3, 4     ; second entry of the table
+                CALLR R3
+                LDA R3, done
+                JMPR R3
+                LDA R1, 100
+            done:
+                FIN
+            first:
+                LDA R0, 1
+                RET
+            second:
+                LDA R0, 2
+                RET
+            handlers: .word first, second
+            ",
+        );
+        assert_eq!(r0, 2);
+        assert_eq!(r1, 0);
+    }
+
+    #[test]
+    fn extended_register_file() {
+        let (controller, result) = run("
+                LDA R15, 40
+                LDA R4, 2
+                ADD R15, R4, R9
+                MOV R9, FP
+                FIN
+            ");
+        assert_eq!(result, Ok(Outcome::Finished));
+        assert_eq!(controller.state().register_value(Register::FP), 42);
+        assert_eq!(Register::from_addr(0x1C), Ok(Register::SP));
+        assert_eq!(Register::from_addr(0x54), Ok(Register::R15));
+        assert_eq!(Register::from_addr(0x58), Ok(Register::IVT));
+        assert_eq!(Register::header_size(), 0x20);
+
+        // FP and R4..R15 are not mapped to the code that follows the header
+        let state = controller.state();
+        assert_eq!(state.read_word(code_address(0)), Ok(0x0028_540C));
+        assert_eq!(state.read_word(code_address(1)), Ok(0x0002_280C));
+    }
+
+    #[test]
+    fn stack_frames() {
+        let source = "
+                LDA R0, 10
+                PUSH R0
+                LDA R0, 20
+                PUSH R0
+                LDA R0, 30
+                PUSH R0
+                LDA R0, 40
+                PUSH R0
+                LDA R0, 50
+                PUSH R0
+                CALL sum_first_last
+                SUBI SP, 20
+                FIN
+            sum_first_last:
+                ENTER 4
+                LDF R0, -12         ; the last argument
+                LDF R1, -28         ; the first argument
+                ADD R0, R1, R0
+                STF R0, 0           ; the local variable
+                LDS R2, -4
+                LEAVE
+                RET
+            ";
+        let (controller, result) = run(source);
+        assert_eq!(result, Ok(Outcome::Finished));
+
+        let image = assemble(source).unwrap();
+        let initial_sp = State::new(BufferMemory::from_bytes(&image))
+            .unwrap()
+            .register_value(Register::SP);
+        let state = controller.state();
+        assert_eq!(state.register_value(Register::R2), 60);
+        assert_eq!(state.register_value(Register::FP), 0);
+        assert_eq!(state.register_value(Register::SP), initial_sp);
+        assert_eq!(state.read_word(initial_sp + 24), Ok(0));
+        assert_eq!(state.read_word(initial_sp + 28), Ok(60));
+    }
+
+    /// Runs an image built for the original header with [code] at 0x28,
+    /// SP at [sp_value] and the free space at 0x20..0x28.
+    fn run_raw(sp_value: u32, code: &[u8]) -> (Controller, Result<Outcome, VmError>) {
+        let mut image = vec![0u8; 0x28];
+        image[0..4].copy_from_slice(&0x28u32.to_le_bytes());
+        image[0x1C..0x20].copy_from_slice(&sp_value.to_le_bytes());
+        image.extend_from_slice(code);
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let mut controller = Controller::new(state);
+        let result = controller.execute();
+        (controller, result)
+    }
+
+    #[test]
+    fn pushes_into_code_fault() {
+        const PUSH_R0: [u8; 4] = [0x15, 0x04, 0, 0];
+
+        // No stack: SP points to the header
+        let (controller, result) = run_raw(0, &PUSH_R0);
+        assert_eq!(result, Err(VmError::StackOverflow { ip: 0x28, sp: 0 }));
+        assert_eq!(controller.state().register_value(Register::IP), 0x28);
+
+        // The stack at 0x20..0x28 is followed by the code
+        let code = [PUSH_R0, PUSH_R0, PUSH_R0].concat();
+        let (controller, result) = run_raw(0x20, &code);
+        assert_eq!(result, Err(VmError::StackOverflow { ip: 0x30, sp: 0x28 }));
+        let memory = controller.state().get_memory_handler();
+        assert_eq!(memory.read_word(0x28).unwrap(), PUSH_R0);
+
+        // SP moved below the stack by the program
+        let (_, result) = run("
+                LDA R0, 0
+                MOV R0, SP
+                PUSH R1
+            ");
+        assert_eq!(
+            result,
+            Err(VmError::StackOverflow {
+                ip: code_address(2),
+                sp: 0
+            })
+        );
+        assert_eq!(assemble(".stack 16\nFIN").unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn stack_bounds_are_checked() {
+        let (_, result) = run("
+            recurse:
+                CALL recurse
+                .stack 16
+            ");
+        assert_eq!(
+            result,
+            Err(VmError::StackOverflow {
+                ip: code_address(0),
+                sp: code_address(1) + 16
+            })
+        );
+
+        let (_, result) = run("
+                POP R0
+                FIN
+            ");
+        assert_eq!(
+            result,
+            Err(VmError::StackUnderflow {
+                ip: code_address(0),
+                sp: code_address(2)
+            })
+        );
+
+        let image = assemble(
+            "
+                ENTER 8
+                FIN
+            ",
+        )
+        .unwrap();
+        let mut state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let base = state.stack_bounds().base;
+        state.set_stack_bounds(StackBounds {
+            base,
+            limit: base + 8,
+        });
+        let mut controller = Controller::new(state);
+        assert_eq!(
+            controller.execute(),
+            Err(VmError::StackOverflow {
+                ip: code_address(0),
+                sp: base + 4
+            })
+        );
+    }
+
+    fn flags_after(source: &str) -> [bool; 4] {
+        let (controller, result) = run(source);
+        assert_eq!(result, Ok(Outcome::Finished));
+        [Flag::Zero, Flag::Sign, Flag::Carry, Flag::Overflow]
+            .map(|flag| controller.state().flag(flag))
+    }
+
+    #[test]
+    fn arithmetic_updates_flags() {
+        // [zero, sign, carry, overflow]
+        let add_carry = flags_after(
+            "
+                LDA R0, 1
+                SUB R1, R0, R1      ; R1 = 0xFFFFFFFF
+                ADD R1, R0, R2
+                FIN
+            ",
+        );
+        assert_eq!(add_carry, [true, false, true, false]);
+
+        let sub_borrow = flags_after(
+            "
+                LDA R0, 1
+                SUB R1, R0, R1
+                FIN
+            ",
+        );
+        assert_eq!(sub_borrow, [false, true, true, false]);
+
+        let signed_overflow = flags_after(
+            "
+                LDA R0, 0x4000
+                MUL R0, R0, R0      ; R0 = 0x10000000
+                ADD R0, R0, R0
+                ADD R0, R0, R0
+                ADD R0, R0, R0      ; 0x40000000 + 0x40000000
+                FIN
+            ",
+        );
+        assert_eq!(signed_overflow, [false, true, false, true]);
+
+        let mul_carry = flags_after(
+            "
+                LDA R0, 0x1000
+                MUL R0, R0, R1      ; R1 = 0x1000000
+                MUL R1, R0, R1
+                FIN
+            ",
+        );
+        assert_eq!(mul_carry, [true, false, true, true]);
+    }
+
+    #[test]
+    fn comparisons_update_flags() {
+        let equal = flags_after(
+            "
+                LDA R0, 5
+                LDA R1, 5
+                EQ R0, R1
+                FIN
+            ",
+        );
+        assert_eq!(equal, [true, false, false, false]);
+
+        let below = flags_after(
+            "
+                LDA R0, 3
+                LDA R1, 5
+                L R0, R1
+                FIN
+            ",
+        );
+        assert_eq!(below, [false, true, true, false]);
+    }
+
+    #[test]
+    fn jumps_on_flags() {
+        let [r0, r1, _, _] = registers_after(
+            "
+                LDA R0, 10
+                LDA R2, 1
+            loop:
+                ADD R1, R0, R1
+                SUB R0, R2, R0
+                JNZ loop
+                FIN
+            ",
+        );
+        assert_eq!(r0, 0);
+        assert_eq!(r1, 55);
+
+        // 64-bit addition: (R1:R0) += (R3:R2)
+        let [r0, r1, _, _] = registers_after(
+            "
+                LDA R0, 0
+                LDA R2, 1
+                SUB R0, R2, R0      ; R0 = 0xFFFFFFFF
+                LDA R1, 1
+                LDA R3, 2
+                ADD R0, R2, R0
+                JNC no_carry
+                ADD R1, R2, R1
+            no_carry:
+                ADD R1, R3, R1
+                FIN
+            ",
+        );
+        assert_eq!(r0, 0);
+        assert_eq!(r1, 4);
+    }
+
+    #[test]
+    fn flags_do_not_overwrite_code() {
+        // The first instruction sits at the address of FLAGS
+        let (controller, result) = run("
+            start:
+                ADD R0, R2, R0
+                LDA R2, 1
+                LDA R3, 2
+                L R0, R3
+                JCMP start
+                FIN
+            ");
+
+        assert_eq!(result, Ok(Outcome::Finished));
+        assert_eq!(code_address(0), Register::FLAGS.as_addr());
+        let state = controller.state();
+        assert_eq!(state.register_value(Register::R0), 2);
+        assert!(state.flag(Flag::Zero));
+        assert_eq!(state.read_word(code_address(0)), Ok(0x040C_0401));
+    }
+
+    #[test]
+    fn signed_number_io() {
+        let image = assemble(
+            "
+                INPNI R0
+                INPNI R1
+                ADD R0, R1, R2
+                LDA R3, ' '
+                OUTNI R2
+                OUTR R3
+                OUTNI R0
+                OUTR R3
+                OUTN R0
+                FIN
+            ",
+        )
+        .unwrap();
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let display = ScriptedDisplay::scripted("-2147483648\n5\n");
+        let output = display.output();
+        let mut controller = Controller::builder(state).display(display).build();
+
+        assert_eq!(controller.execute(), Ok(Outcome::Finished));
+        assert_eq!(output.contents(), "-2147483643 -2147483648 2147483648");
+    }
+
+    #[test]
+    fn signed_comparison_boundaries() {
+        let prologue = "
+                LDA R0, 1
+                LDA R1, 31
+                SHL R0, R1, R2      ; R2 = i32::MIN
+                SUB R2, R0, R3      ; R3 = i32::MAX
+            ";
+        let [r0, r1, r2, r3] = registers_after(&format!(
+            "{}
+                ILE R2, R3
+                MOV CMP, R0
+                ILE R3, R2
+                MOV CMP, R1
+                FIN
+            ",
+            prologue
+        ));
+        assert_eq!(r2 as i32, i32::MIN);
+        assert_eq!(r3 as i32, i32::MAX);
+        assert_eq!(r0, 1);
+        assert_eq!(r1, 0);
+
+        let [r0, r1, ..] = registers_after(&format!(
+            "{}
+                IGT R3, R2
+                MOV CMP, R0
+                IGT R2, R3
+                MOV CMP, R1
+                FIN
+            ",
+            prologue
+        ));
+        assert_eq!(r0, 1);
+        assert_eq!(r1, 0);
+
+        let [r0, r1, r2, r3] = registers_after(
+            "
+                LDA R0, 1
+                SUB R1, R0, R1      ; R1 = -1
+                LDA R0, 0
+                ILE R1, R1
+                MOV CMP, R2
+                IGT R0, R1
+                MOV CMP, R3
+                IGT R1, R0
+                MOV CMP, R0
+                FIN
+            ",
+        );
+        assert_eq!(r1 as i32, -1);
+        assert_eq!(r2, 1);
+        assert_eq!(r3, 1);
+        assert_eq!(r0, 0);
+    }
+
+    #[test]
+    fn word_access_has_to_be_aligned() {
+        let (_, result) = run("
+                LDA R1, 2
+                STW R0, R1
+                FIN
+            ");
+        assert_eq!(
+            result,
+            Err(VmError::MisalignedAccess {
+                ip: code_address(1),
+                address: 2
+            })
+        );
+    }
+}
@@ -1,7 +1,104 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
 use crate::vm::arch::instruction::decode;
 use crate::vm::arch::ARCH_BYTES;
 use crate::vm::components::display::{Display, SystemDisplay};
-use crate::vm::components::state::{Register, State};
+use crate::vm::components::state::{Flag, Register, State};
+use crate::vm::components::syscalls::{standard_syscalls, SyscallHandler};
+use crate::vm::components::tracer::{TraceEvent, Tracer};
+use crate::vm::error::{Fault, VmError};
+
+/// Number of interrupt lines, the vector table holds a handler address for each of them.
+pub const INTERRUPT_LINES: u32 = 32;
+
+/// # Limit
+/// Execution limit of the [Controller].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Limit {
+    Steps,
+    Time,
+}
+
+/// # Outcome
+/// Reports why [Controller::execute] returned without a fault:
+/// either the program has finished, or it has exited with a [code]
+/// through [Controller::exit], or it has run out of the
+/// [Limit] after [steps] steps and can be resumed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Finished,
+    Exited { code: u32 },
+    LimitExceeded { limit: Limit, steps: u64 },
+}
+
+/// # ControllerBuilder
+/// Configures a [Controller] before it is created:
+/// ```ignore
+/// let controller = Controller::builder(state)
+///     .display(ScriptedDisplay::scripted("5\n"))
+///     .max_steps(1000)
+///     .build();
+/// ```
+/// The [SystemDisplay] is used if no display is given.
+pub struct ControllerBuilder {
+    state: State,
+    display: Option<Box<dyn Display>>,
+    tracer: Option<Box<dyn Tracer>>,
+    syscalls: HashMap<u32, SyscallHandler>,
+    max_steps: Option<u64>,
+    timeout: Option<Duration>,
+}
+
+impl ControllerBuilder {
+    pub fn display(mut self, display: impl Display + 'static) -> Self {
+        self.display = Some(Box::new(display));
+        self
+    }
+
+    pub fn tracer(mut self, tracer: Box<dyn Tracer>) -> Self {
+        self.tracer = Some(tracer);
+        self
+    }
+
+    /// Adds a handler for the syscall [number] or replaces the standard one.
+    pub fn syscall(
+        mut self,
+        number: u32,
+        handler: impl FnMut(&mut Controller) -> Result<(), Fault> + 'static,
+    ) -> Self {
+        self.syscalls.insert(number, Box::new(handler));
+        self
+    }
+
+    pub fn max_steps(mut self, max_steps: u64) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn build(self) -> Controller {
+        let initial_ip_value = self.state.register_value(Register::IP);
+        Controller {
+            state: self.state,
+            display: self
+                .display
+                .unwrap_or_else(|| Box::new(SystemDisplay::new())),
+            tracer: self.tracer,
+            syscalls: self.syscalls,
+            exit_code: None,
+            initial_ip_value,
+            steps: 0,
+            pending_interrupts: 0,
+            max_steps: self.max_steps,
+            timeout: self.timeout,
+        }
+    }
+}
 
 /// # Controller
 /// Simulates controller component of the virtual machine.
@@ -13,27 +110,115 @@ use crate::vm::components::state::{Register, State};
 /// - decode
 /// - execute
 ///
+/// A fault in any stage stops the pipeline and is reported
+/// as a [VmError] with the IP of the faulting instruction.
+///
+/// If a [Tracer] is set, every step is reported to it,
+/// taking an interrupt is reported as `INTERRUPT <line>`.
+///
+/// [execute] can be limited by the number of steps and by time,
+/// see [set_max_steps] and [set_timeout].
+///
+/// Interrupts are raised by the devices after each step or by
+/// [raise_interrupt]. While the Interrupt flag is set, the pending
+/// interrupt with the lowest line is taken before the next step:
+/// FLAGS and IP are pushed to the stack, the flag is cleared and
+/// the execution jumps to the handler from the vector table at IVT.
+/// Interrupts without a handler (address 0) are dropped. While IVT
+/// is 0, the interrupts are not configured and stay pending.
+///
+/// SYSCALL calls the host handler registered for the number in R0,
+/// the standard ones are listed in [syscalls](crate::vm::components::syscalls).
+///
 pub struct Controller {
     state: State,
     display: Box<dyn Display>,
+    tracer: Option<Box<dyn Tracer>>,
+    syscalls: HashMap<u32, SyscallHandler>,
+    exit_code: Option<u32>,
     initial_ip_value: u32,
+    steps: u64,
+    pending_interrupts: u32,
+    max_steps: Option<u64>,
+    timeout: Option<Duration>,
 }
 
 impl Controller {
+    /// Creates a controller with the [SystemDisplay],
+    /// use [Controller::builder] to configure it.
     pub fn new(state: State) -> Self {
-        let initial_ip_value = state.register_value(Register::IP);
-        Controller {
+        Controller::builder(state).build()
+    }
+
+    pub fn builder(state: State) -> ControllerBuilder {
+        ControllerBuilder {
             state,
-            display: Box::new(SystemDisplay::new()),
-            initial_ip_value,
+            display: None,
+            tracer: None,
+            syscalls: standard_syscalls(),
+            max_steps: None,
+            timeout: None,
         }
     }
 
-    pub fn execute(&mut self) {
+    pub fn set_tracer(&mut self, tracer: Box<dyn Tracer>) {
+        self.tracer = Some(tracer);
+    }
+
+    /// Number of steps executed so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Limits the number of steps made by a single [execute] call.
+    pub fn set_max_steps(&mut self, max_steps: Option<u64>) {
+        self.max_steps = max_steps;
+    }
+
+    /// Limits the time taken by a single [execute] call.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Runs the program until it finishes, faults or runs out of a limit.
+    /// The display is flushed in any case, so the output is complete
+    /// and write errors are reported.
+    pub fn execute(&mut self) -> Result<Outcome, VmError> {
+        let result = self.run();
+        let ip_value = self.state.register_value(Register::IP);
+        let flushed = self.flush().map_err(|fault| fault.at(ip_value));
+        let outcome = result?;
+        flushed?;
+        if !matches!(outcome, Outcome::LimitExceeded { .. }) {
+            self.reset_machine();
+        }
+        Ok(outcome)
+    }
+
+    fn run(&mut self) -> Result<Outcome, VmError> {
+        let first_step = self.steps;
+        let start = Instant::now();
         while !self.is_finished() {
-            self.step()
+            let steps = self.steps - first_step;
+            let limit = if self.max_steps.is_some_and(|max_steps| steps >= max_steps) {
+                Some(Limit::Steps)
+            } else if self
+                .timeout
+                .is_some_and(|timeout| start.elapsed() >= timeout)
+            {
+                Some(Limit::Time)
+            } else {
+                None
+            };
+            if let Some(limit) = limit {
+                return Ok(Outcome::LimitExceeded { limit, steps });
+            }
+            self.step()?;
         }
-        self.reset_machine();
+        Ok(match self.exit_code.take() {
+            Some(code) => Outcome::Exited { code },
+            None => Outcome::Finished,
+        })
     }
 
     pub fn state(&self) -> &State {
@@ -52,14 +237,54 @@ impl Controller {
         self.display.as_mut()
     }
 
+    /// Prints [c] to the display.
+    pub fn print(&mut self, c: char) -> Result<(), Fault> {
+        self.display
+            .print(c)
+            .map_err(|error| Fault::OutputError(error.to_string()))
+    }
+
+    /// Writes out the output buffered by the display.
+    pub fn flush(&mut self) -> Result<(), Fault> {
+        self.display
+            .flush()
+            .map_err(|error| Fault::OutputError(error.to_string()))
+    }
+
+    /// Adds a handler for the syscall [number] or replaces the existing one.
+    pub fn register_syscall(
+        &mut self,
+        number: u32,
+        handler: impl FnMut(&mut Controller) -> Result<(), Fault> + 'static,
+    ) {
+        self.syscalls.insert(number, Box::new(handler));
+    }
+
+    /// Calls the handler of the syscall [number].
+    pub fn syscall(&mut self, number: u32) -> Result<(), Fault> {
+        let mut handler = self
+            .syscalls
+            .remove(&number)
+            .ok_or(Fault::UnknownSyscall(number))?;
+        let result = handler(self);
+        // The handler may have registered a replacement for itself.
+        self.syscalls.entry(number).or_insert(handler);
+        result
+    }
+
+    /// Stops the program, [execute] reports the exit [code].
+    pub fn exit(&mut self, code: u32) {
+        self.exit_code = Some(code);
+        self.state.set_register_value(Register::END, 1);
+    }
+
     pub fn jump_abs(&mut self, ip_value: u32) {
-        self.mut_state()
-            .set_register_value(Register::IP, ip_value);
+        self.mut_state().set_register_value(Register::IP, ip_value);
     }
 
     pub fn jump(&mut self, offset: i16) {
         let ip_value = self.state().register_value(Register::IP);
-        let address = (ip_value as i32 + offset as i32) as u32;
+        let address = ip_value.wrapping_add_signed(offset as i32);
         self.jump_abs(address);
     }
 
@@ -69,27 +294,393 @@ impl Controller {
         self.state.set_register_value(Register::END, 0);
     }
 
-    fn step(&mut self) {
-        let instruction = self.fetch();
-        let mut command = decode(instruction);
-        command.execute(self);
+    /// Marks the interrupt [line] as pending, lines wrap around [INTERRUPT_LINES].
+    pub fn raise_interrupt(&mut self, line: u32) {
+        self.pending_interrupts |= 1 << (line % INTERRUPT_LINES);
+    }
+
+    /// Bit mask of the interrupt lines waiting to be taken.
+    pub fn pending_interrupts(&self) -> u32 {
+        self.pending_interrupts
+    }
+
+    /// Finds the pending interrupt to take and its handler,
+    /// dropping the lines without a handler on the way.
+    fn next_interrupt(&mut self) -> Result<Option<(u32, u32)>, Fault> {
+        let vector_table = self.state.register_value(Register::IVT);
+        if !self.state.flag(Flag::Interrupt) || vector_table == 0 {
+            return Ok(None);
+        }
+        while self.pending_interrupts != 0 {
+            let line = self.pending_interrupts.trailing_zeros();
+            let handler = self
+                .state
+                .read_word(vector_table.wrapping_add(line * ARCH_BYTES))?;
+            if handler != 0 {
+                return Ok(Some((line, handler)));
+            }
+            self.pending_interrupts &= !(1 << line);
+        }
+        Ok(None)
+    }
+
+    /// Enters the [handler] of the interrupt [line], the line stays
+    /// pending if FLAGS or IP can't be pushed to the stack.
+    fn take_interrupt(&mut self, line: u32, handler: u32) -> Result<(), Fault> {
+        self.state.push_to_stack(Register::FLAGS)?;
+        self.state.push_to_stack(Register::IP)?;
+        self.pending_interrupts &= !(1 << line);
+        self.state.set_flag(Flag::Interrupt, false);
+        self.jump_abs(handler);
+        Ok(())
+    }
+
+    pub fn step(&mut self) -> Result<(), VmError> {
+        let ip_value = self.state.register_value(Register::IP);
+        let interrupt = self.next_interrupt().map_err(|fault| fault.at(ip_value))?;
+        if let Some((line, handler)) = interrupt {
+            let mnemonic = format!("INTERRUPT {}", line);
+            self.traced(ip_value, &mnemonic, |controller| {
+                controller.take_interrupt(line, handler)
+            })?;
+        }
+        let ip_value = self.state.register_value(Register::IP);
+        let result = if self.tracer.is_some() {
+            let mnemonic = match self.fetch(ip_value).and_then(decode) {
+                Ok(command) => command.to_string(),
+                Err(_) => "<invalid>".to_string(),
+            };
+            self.traced(ip_value, &mnemonic, |controller| controller.cycle(ip_value))
+        } else {
+            self.cycle(ip_value).map_err(|fault| fault.at(ip_value))
+        };
+        self.steps += 1;
+        self.pending_interrupts |= self.state.tick_devices();
+        result
+    }
+
+    /// Runs [action] and reports it to the tracer, if any,
+    /// with the registers and the memory writes it changed.
+    fn traced<F>(&mut self, ip_value: u32, mnemonic: &str, action: F) -> Result<(), VmError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), Fault>,
+    {
+        if self.tracer.is_none() {
+            return action(self).map_err(|fault| fault.at(ip_value));
+        }
+        let registers_before = self.registers();
+
+        self.state.start_journal();
+        let result = action(self).map_err(|fault| fault.at(ip_value));
+        let memory_writes = self.state.take_journal();
+
+        let registers_after = self.registers();
+        let event = TraceEvent {
+            step: self.steps,
+            ip: ip_value,
+            mnemonic,
+            registers_before: &registers_before,
+            registers_after: &registers_after,
+            memory_writes: &memory_writes,
+            error: result.as_ref().err(),
+        };
+        if let Some(tracer) = self.tracer.as_mut() {
+            tracer
+                .trace(&event)
+                .map_err(|error| Fault::TraceError(error.to_string()).at(ip_value))?;
+        }
+        result
+    }
+
+    fn registers(&self) -> Vec<u32> {
+        Register::ALL
+            .iter()
+            .map(|register| self.state.register_value(*register))
+            .collect()
+    }
+
+    fn cycle(&mut self, ip_value: u32) -> Result<(), Fault> {
+        let instruction = self.fetch(ip_value)?;
+        let mut command = decode(instruction)?;
+        command.execute(self)?;
         if command.move_ip() {
             self.next();
         }
+        Ok(())
     }
 
     fn next(&mut self) {
         let ip_value = self.state.register_value(Register::IP);
         self.state
-            .set_register_value(Register::IP, ip_value + ARCH_BYTES);
+            .set_register_value(Register::IP, ip_value.wrapping_add(ARCH_BYTES));
     }
 
-    fn fetch(&mut self) -> &[u8] {
-        let ip_value = self.state.register_value(Register::IP);
+    fn fetch(&self, ip_value: u32) -> Result<&[u8], Fault> {
         self.state.get_memory_handler().read_word(ip_value)
     }
 
-    fn is_finished(&self) -> bool {
+    pub fn is_finished(&self) -> bool {
         self.state.register_value(Register::END) != 0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::io::{BufWriter, Cursor, Write};
+    use std::time::Duration;
+
+    use crate::vm::components::controller::{Limit, Outcome};
+    use crate::vm::components::devices::IntervalTimerDevice;
+    use crate::vm::components::display::{ScriptedDisplay, SharedOutput, StreamDisplay};
+    use crate::vm::components::state::StackBounds;
+    use crate::vm::components::tracer::TextTracer;
+    use crate::vm::tools::assembler::assemble;
+    use crate::{BufferMemory, Controller, Flag, Register, State, VmError};
+
+    #[test]
+    fn runs_headless_with_scripted_display() {
+        let image = assemble(
+            "
+                INPN R0
+                INP R1
+                OUTN R0
+                OUTR R1
+                LDA R0, message
+                OUT R0
+                FIN
+            message: .string \"!\\n\"
+            ",
+        )
+        .unwrap();
+        let display = ScriptedDisplay::scripted("42\nx\n");
+        let output = display.output();
+        let mut controller =
+            Controller::builder(State::new(BufferMemory::from_bytes(&image)).unwrap())
+                .display(display)
+                .max_steps(100)
+                .build();
+
+        assert_eq!(controller.execute(), Ok(Outcome::Finished));
+        assert_eq!(output.contents(), "42x!\n");
+    }
+
+    fn endless_loop() -> Controller {
+        let image = assemble("loop: JMP loop").unwrap();
+        Controller::new(State::new(BufferMemory::from_bytes(&image)).unwrap())
+    }
+
+    #[test]
+    fn stops_on_limits() {
+        let mut controller = endless_loop();
+        controller.set_max_steps(Some(100));
+        assert_eq!(
+            controller.execute(),
+            Ok(Outcome::LimitExceeded {
+                limit: Limit::Steps,
+                steps: 100
+            })
+        );
+        assert_eq!(controller.execute().unwrap(), controller.execute().unwrap());
+        assert_eq!(controller.steps(), 300);
+
+        let mut controller = endless_loop();
+        controller.set_timeout(Some(Duration::from_millis(10)));
+        let outcome = controller.execute().unwrap();
+        assert!(matches!(
+            outcome,
+            Outcome::LimitExceeded {
+                limit: Limit::Time,
+                steps
+            } if steps > 0
+        ));
+    }
+
+    /// Output that fails like a full disk.
+    struct FullDisk;
+
+    impl Write for FullDisk {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::StorageFull, "No space left"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reports_buffered_output_errors() {
+        let image = assemble("LDA R0, 'x'\nOUTR R0\nFIN").unwrap();
+        let display = StreamDisplay::new(Cursor::new(Vec::new()), BufWriter::new(FullDisk));
+        let mut controller =
+            Controller::builder(State::new(BufferMemory::from_bytes(&image)).unwrap())
+                .display(display)
+                .build();
+        assert!(matches!(
+            controller.execute(),
+            Err(VmError::OutputError { message, .. }) if message == "No space left"
+        ));
+    }
+
+    #[test]
+    fn takes_timer_interrupts() {
+        // The handler may run between CMPI and JNZ,
+        // so the loop relies on IRET restoring FLAGS.
+        let image = assemble(
+            "
+                LDA R0, vectors
+                MOV R0, IVT
+                LDA R3, 0x1000
+                LDA R0, 1
+                STW R0, R3, 4       ; line 1
+                LDA R0, 5
+                STW R0, R3          ; every 5 instructions
+                EI
+            loop:
+                CMPI R1, 3
+                JNZ loop
+                DI
+                FIN
+            tick:
+                ADDI R1, 1
+                CMPI R1, 3
+                JNZ done
+                STW R2, R3          ; stop the timer
+            done:
+                IRET
+            vectors: .word 0, tick
+            ",
+        )
+        .unwrap();
+        let mut state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        state
+            .attach_device(0x1000, IntervalTimerDevice::new())
+            .unwrap();
+        let initial_sp = state.register_value(Register::SP);
+        let mut controller = Controller::builder(state).max_steps(1000).build();
+
+        assert_eq!(controller.execute(), Ok(Outcome::Finished));
+        let state = controller.state();
+        assert_eq!(state.register_value(Register::R1), 3);
+        assert_eq!(state.register_value(Register::SP), initial_sp);
+        assert!(!state.flag(Flag::Interrupt));
+    }
+
+    #[test]
+    fn defers_interrupts_while_disabled() {
+        let image = assemble(
+            "
+                LDA R0, vectors
+                MOV R0, IVT
+                LDA R1, 1
+                EI
+                LDA R1, 2
+                FIN
+            handler:
+                MOV R1, R2
+                IRET
+            vectors: .word 0, 0, handler
+            ",
+        )
+        .unwrap();
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let mut controller = Controller::new(state);
+        controller.raise_interrupt(1);
+        controller.raise_interrupt(2);
+        for _ in 0..3 {
+            controller.step().unwrap();
+        }
+        assert_eq!(controller.pending_interrupts(), 0b110);
+
+        assert_eq!(controller.execute(), Ok(Outcome::Finished));
+        assert_eq!(controller.pending_interrupts(), 0);
+        assert_eq!(controller.state().register_value(Register::R2), 1);
+    }
+
+    #[test]
+    fn keeps_interrupts_pending_without_vector_table() {
+        let image = assemble(
+            "
+                EI
+                LDA R0, 1
+                LDA R1, 2
+                FIN
+            ",
+        )
+        .unwrap();
+        let mut state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        state
+            .attach_device(0x1000, IntervalTimerDevice::with_period(1, 3))
+            .unwrap();
+        let mut controller = Controller::new(state);
+
+        assert_eq!(controller.execute(), Ok(Outcome::Finished));
+        assert_eq!(controller.pending_interrupts(), 1 << 3);
+        let state = controller.state();
+        assert_eq!(state.register_value(Register::R0), 1);
+        assert_eq!(state.register_value(Register::R1), 2);
+        assert_eq!(
+            state.register_value(Register::SP),
+            state.stack_bounds().base
+        );
+    }
+
+    #[test]
+    fn keeps_interrupts_pending_on_faults() {
+        let image = assemble(
+            "
+                LDA R0, vectors
+                MOV R0, IVT
+                EI
+                FIN
+            handler:
+                IRET
+            vectors: .word handler
+            ",
+        )
+        .unwrap();
+        let mut state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let base = state.stack_bounds().base;
+        // room for FLAGS only
+        state.set_stack_bounds(StackBounds {
+            base,
+            limit: base + 4,
+        });
+        let mut controller = Controller::new(state);
+        controller.raise_interrupt(0);
+
+        assert!(matches!(
+            controller.execute(),
+            Err(VmError::StackOverflow { .. })
+        ));
+        assert_eq!(controller.pending_interrupts(), 1);
+    }
+
+    #[test]
+    fn traces_interrupts() {
+        let image = assemble(
+            "
+                LDA R0, vectors
+                MOV R0, IVT
+                EI
+                FIN
+            handler:
+                IRET
+            vectors: .word 0, 0, handler
+            ",
+        )
+        .unwrap();
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let output = SharedOutput::new();
+        let mut controller = Controller::builder(state)
+            .tracer(Box::new(TextTracer::new(output.clone())))
+            .build();
+        controller.raise_interrupt(2);
+
+        assert_eq!(controller.execute(), Ok(Outcome::Finished));
+        let trace = output.contents();
+        assert!(trace.contains("#3 0x002c: INTERRUPT 2\n"));
+        assert!(trace.contains("#3 0x0030: IRET\n"));
+    }
+}
@@ -0,0 +1,430 @@
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use crate::vm::components::controller::INTERRUPT_LINES;
+use crate::vm::components::display::Display;
+use crate::vm::components::state::State;
+use crate::vm::error::Fault;
+
+/// Address the CLI maps the standard devices to, see [attach_standard_devices].
+pub const DEFAULT_MMIO_BASE: u32 = 0xFFFF_0000;
+
+/// Maps the console to [base], the timer to [base + 0x10],
+/// the random number generator to [base + 0x20]
+/// and the interval timer to [base + 0x30].
+pub fn attach_standard_devices(
+    state: &mut State,
+    base: u32,
+    display: impl Display + 'static,
+) -> Result<(), Fault> {
+    state.attach_device(base, ConsoleDevice::new(display))?;
+    state.attach_device(base.wrapping_add(0x10), TimerDevice::new())?;
+    state.attach_device(base.wrapping_add(0x20), RngDevice::new())?;
+    state.attach_device(base.wrapping_add(0x30), IntervalTimerDevice::new())
+}
+
+/// # Trait *Device*
+/// Peripheral mapped to the address space of the machine,
+/// see [State::attach_device](crate::vm::components::state::State::attach_device).
+/// Loads and stores in its range are turned into [read] and [write]
+/// calls with the offset from the start of the range.
+/// Byte accesses read and write the lowest byte of a register.
+pub trait Device {
+    /// Number of bytes the device occupies, a multiple of the word size.
+    fn size(&self) -> u32;
+    fn read(&mut self, offset: u32) -> Result<u32, Fault>;
+    fn write(&mut self, offset: u32, value: u32) -> Result<(), Fault>;
+
+    /// Called after every executed instruction. Returns the interrupt
+    /// line to raise, if any, it wraps around [INTERRUPT_LINES].
+    fn tick(&mut self) -> Option<u32> {
+        None
+    }
+}
+
+/// # ConsoleDevice
+/// Gives access to a [Display] through the memory.
+///
+/// Registers:
+/// - 0x0: loads read a character, stores print a character
+/// - 0x4: loads read a number, stores print a number
+///
+/// Only the accesses at the start of a register use the display,
+/// the other offsets read 0 and ignore stores.
+pub struct ConsoleDevice {
+    display: Box<dyn Display>,
+}
+
+impl ConsoleDevice {
+    pub fn new(display: impl Display + 'static) -> Self {
+        ConsoleDevice {
+            display: Box::new(display),
+        }
+    }
+
+    fn print(&mut self, text: &str) -> Result<(), Fault> {
+        for c in text.chars() {
+            self.display
+                .print(c)
+                .map_err(|error| Fault::OutputError(error.to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+impl Device for ConsoleDevice {
+    fn size(&self) -> u32 {
+        8
+    }
+
+    fn read(&mut self, offset: u32) -> Result<u32, Fault> {
+        match offset {
+            0 => Ok(self.display.get()? as u32),
+            4 => Ok(self.display.get_num()?),
+            _ => Ok(0),
+        }
+    }
+
+    fn write(&mut self, offset: u32, value: u32) -> Result<(), Fault> {
+        match offset {
+            0 => {
+                let c = char::from_u32(value).ok_or(Fault::InvalidCharacter(value))?;
+                self.print(&c.to_string())
+            }
+            4 => self.print(&value.to_string()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// # TimerDevice
+/// Measures the time since it was attached or reset.
+///
+/// Registers:
+/// - 0x0: loads read the elapsed milliseconds, stores reset the timer
+/// - 0x4: loads read the seconds since the Unix epoch
+pub struct TimerDevice {
+    start: Instant,
+}
+
+#[allow(clippy::new_without_default)]
+impl TimerDevice {
+    pub fn new() -> Self {
+        TimerDevice {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Device for TimerDevice {
+    fn size(&self) -> u32 {
+        8
+    }
+
+    fn read(&mut self, offset: u32) -> Result<u32, Fault> {
+        match offset {
+            0..=3 => Ok(self.start.elapsed().as_millis() as u32),
+            _ => Ok(SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |time| time.as_secs() as u32)),
+        }
+    }
+
+    fn write(&mut self, offset: u32, _value: u32) -> Result<(), Fault> {
+        if offset < 4 {
+            self.start = Instant::now();
+        }
+        Ok(())
+    }
+}
+
+/// # RngDevice
+/// Pseudo-random number generator (xorshift32).
+///
+/// Registers:
+/// - 0x0: loads read the next number, stores set the seed
+pub struct RngDevice {
+    state: u32,
+}
+
+#[allow(clippy::new_without_default)]
+impl RngDevice {
+    /// Seeds the generator with the current time.
+    pub fn new() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.subsec_nanos());
+        RngDevice::with_seed(nanos)
+    }
+
+    pub fn with_seed(seed: u32) -> Self {
+        let mut rng = RngDevice { state: 0 };
+        rng.seed(seed);
+        rng
+    }
+
+    /// Xorshift gets stuck on zero, so it is replaced.
+    fn seed(&mut self, seed: u32) {
+        self.state = if seed == 0 { 0x9E37_79B9 } else { seed };
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+}
+
+impl Device for RngDevice {
+    fn size(&self) -> u32 {
+        4
+    }
+
+    fn read(&mut self, _offset: u32) -> Result<u32, Fault> {
+        Ok(self.next_u32())
+    }
+
+    fn write(&mut self, _offset: u32, value: u32) -> Result<(), Fault> {
+        self.seed(value);
+        Ok(())
+    }
+}
+
+/// # IntervalTimerDevice
+/// Raises an interrupt every [period] executed instructions,
+/// see [Controller](crate::vm::components::controller::Controller).
+///
+/// Registers:
+/// - 0x0: the period, stores restart the countdown, 0 stops the timer
+/// - 0x4: the interrupt line, from 0 to 31
+/// - 0x8: loads read the instructions left until the next interrupt
+pub struct IntervalTimerDevice {
+    period: u32,
+    line: u32,
+    left: u32,
+}
+
+#[allow(clippy::new_without_default)]
+impl IntervalTimerDevice {
+    /// Creates a stopped timer on line 0.
+    pub fn new() -> Self {
+        IntervalTimerDevice::with_period(0, 0)
+    }
+
+    pub fn with_period(period: u32, line: u32) -> Self {
+        IntervalTimerDevice {
+            period,
+            line: line % INTERRUPT_LINES,
+            left: period,
+        }
+    }
+}
+
+impl Device for IntervalTimerDevice {
+    fn size(&self) -> u32 {
+        12
+    }
+
+    fn read(&mut self, offset: u32) -> Result<u32, Fault> {
+        match offset {
+            0..=3 => Ok(self.period),
+            4..=7 => Ok(self.line),
+            _ => Ok(self.left),
+        }
+    }
+
+    fn write(&mut self, offset: u32, value: u32) -> Result<(), Fault> {
+        match offset {
+            0..=3 => {
+                self.period = value;
+                self.left = value;
+            }
+            4..=7 => self.line = value % INTERRUPT_LINES,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self) -> Option<u32> {
+        if self.period == 0 {
+            return None;
+        }
+        self.left -= 1;
+        if self.left > 0 {
+            return None;
+        }
+        self.left = self.period;
+        Some(self.line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vm::components::devices::{
+        ConsoleDevice, Device, IntervalTimerDevice, RngDevice, TimerDevice,
+    };
+    use crate::vm::components::display::{ScriptedDisplay, SharedDisplay};
+    use crate::vm::tools::assembler::assemble;
+    use crate::{BufferMemory, Controller, Fault, NullDisplay, Outcome, Register, State};
+
+    #[test]
+    fn routes_loads_and_stores_to_devices() {
+        let image = assemble(
+            "
+                LDI R3, 0xFFFF0000
+                DEREF R0, R3, 4     ; read a number
+                ADDI R0, 1
+                STW R0, R3, 4       ; print it
+                LDB R1, R3          ; read a character
+                STB R1, R3          ; print it
+                STW R0, R3, 0x10    ; seed the generator
+                DEREF R2, R3, 0x10
+                FIN
+            ",
+        )
+        .unwrap();
+        let mut state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let display = ScriptedDisplay::scripted("41\nz\n");
+        let output = display.output();
+        state
+            .attach_device(0xFFFF_0000, ConsoleDevice::new(display))
+            .unwrap();
+        state.attach_device(0xFFFF_0010, RngDevice::new()).unwrap();
+
+        let mut controller = Controller::builder(state).display(NullDisplay).build();
+        assert_eq!(controller.execute(), Ok(Outcome::Finished));
+        assert_eq!(output.contents(), "42z");
+        assert_eq!(
+            controller.state().register_value(Register::R2),
+            RngDevice::with_seed(42).next_u32()
+        );
+    }
+
+    #[test]
+    fn console_ignores_inner_offsets() {
+        let image = assemble(
+            "
+                LDI R3, 0xFFFF0000
+                LDB R0, R3, 1       ; no input is consumed
+                LDB R1, R3, 5
+                STB R3, R3, 2       ; nothing is printed
+                STB R3, R3, 7
+                LDB R2, R3
+                STB R2, R3
+                FIN
+            ",
+        )
+        .unwrap();
+        let mut state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let display = ScriptedDisplay::scripted("q\n");
+        let output = display.output();
+        state
+            .attach_device(0xFFFF_0000, ConsoleDevice::new(display))
+            .unwrap();
+
+        let mut controller = Controller::builder(state).display(NullDisplay).build();
+        assert_eq!(controller.execute(), Ok(Outcome::Finished));
+        assert_eq!(output.contents(), "q");
+        assert_eq!(controller.state().register_value(Register::R0), 0);
+        assert_eq!(controller.state().register_value(Register::R1), 0);
+    }
+
+    #[test]
+    fn rejects_overlapping_devices() {
+        let image = assemble("FIN").unwrap();
+        let mut state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        state.attach_device(0x1000, TimerDevice::new()).unwrap();
+        assert_eq!(
+            state.attach_device(0x1004, RngDevice::new()),
+            Err(Fault::AddressInUse(0x1004))
+        );
+        assert_eq!(
+            state.attach_device(0x1002, RngDevice::new()),
+            Err(Fault::MisalignedAccess(0x1002))
+        );
+        assert_eq!(
+            state.attach_device(0, RngDevice::new()),
+            Err(Fault::AddressInUse(0))
+        );
+        assert_eq!(
+            state.read_word(0x1006),
+            Err(Fault::MisalignedAccess(0x1006))
+        );
+        assert!(state.read_word(0x1004).unwrap() > 1_000_000_000);
+    }
+
+    #[test]
+    fn timer_resets_on_store() {
+        let mut timer = TimerDevice::new();
+        timer.write(0, 0).unwrap();
+        assert!(timer.read(0).unwrap() < 1000);
+    }
+
+    #[test]
+    fn console_shares_the_display() {
+        let image = assemble(
+            "
+                LDI R3, 0xFFFF0000
+                INP R0
+                LDB R1, R3          ; the rest of the line
+                OUTR R1
+                STB R0, R3
+                FIN
+            ",
+        )
+        .unwrap();
+        let mut state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let scripted = ScriptedDisplay::scripted("ab\n");
+        let output = scripted.output();
+        let display = SharedDisplay::new(scripted);
+        state
+            .attach_device(0xFFFF_0000, ConsoleDevice::new(display.clone()))
+            .unwrap();
+
+        let mut controller = Controller::builder(state).display(display).build();
+        assert_eq!(controller.execute(), Ok(Outcome::Finished));
+        assert_eq!(output.contents(), "ba");
+    }
+
+    #[test]
+    fn interval_timer_counts_instructions() {
+        let mut timer = IntervalTimerDevice::new();
+        assert_eq!(timer.tick(), None);
+        timer.write(4, 33).unwrap();
+        timer.write(0, 3).unwrap();
+        let ticks: Vec<_> = (0..6).map(|_| timer.tick()).collect();
+        assert_eq!(ticks, [None, None, Some(1), None, None, Some(1)]);
+        assert_eq!(timer.read(8).unwrap(), 3);
+    }
+
+    /// Raises an out-of-range line on every step.
+    struct NoisyDevice;
+
+    impl Device for NoisyDevice {
+        fn size(&self) -> u32 {
+            4
+        }
+
+        fn read(&mut self, _offset: u32) -> Result<u32, Fault> {
+            Ok(0)
+        }
+
+        fn write(&mut self, _offset: u32, _value: u32) -> Result<(), Fault> {
+            Ok(())
+        }
+
+        fn tick(&mut self) -> Option<u32> {
+            Some(u32::MAX)
+        }
+    }
+
+    #[test]
+    fn wraps_interrupt_lines() {
+        let image = assemble("FIN").unwrap();
+        let mut state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        state.attach_device(0x1000, NoisyDevice).unwrap();
+        assert_eq!(state.tick_devices(), 1 << 31);
+    }
+}
@@ -1,57 +1,362 @@
+use std::cell::RefCell;
 use std::collections::VecDeque;
+use std::fmt;
+use std::fs::File;
 use std::io;
-use std::io::Write;
+use std::io::{BufRead, BufReader, BufWriter, Cursor, Write};
+use std::path::Path;
+use std::rc::Rc;
 use std::str::FromStr;
 
+/// # Trait *Display*
+/// Input/output device of the machine. Characters are read one by one,
+/// every line ends with '\n'. Numbers take the rest of the current line,
+/// or the next line that is not blank.
 pub trait Display {
-    fn print(&self, c: char);
-    fn get(&mut self) -> char;
-    fn get_num(&mut self) -> u32;
+    fn print(&mut self, c: char) -> io::Result<()>;
+    fn get(&mut self) -> io::Result<char>;
+    /// Reads the rest of the current line.
+    fn get_line(&mut self) -> io::Result<String>;
+    fn get_num(&mut self) -> io::Result<u32>;
+    fn get_signed_num(&mut self) -> io::Result<i32>;
+    /// Writes out the buffered output, reporting the errors
+    /// that buffered writes can't report when they happen.
+    fn flush(&mut self) -> io::Result<()>;
 }
 
-pub struct SystemDisplay {
+/// Characters of the current input line that are not read yet.
+struct LineBuffer {
     buffer: VecDeque<char>,
 }
 
-#[allow(clippy::new_without_default)]
-impl SystemDisplay {
-    pub fn new() -> Self {
-        SystemDisplay {
+impl LineBuffer {
+    fn new() -> Self {
+        LineBuffer {
             buffer: VecDeque::new(),
         }
     }
 
-    fn check_fill_buffer(&mut self) {
+    fn fill(&mut self, input: &mut impl BufRead) -> io::Result<()> {
         if self.buffer.is_empty() {
             let mut line = String::new();
-            let _ = io::stdin().read_line(&mut line).unwrap();
+            if input.read_line(&mut line)? == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Input is closed",
+                ));
+            }
+            self.buffer
+                .extend(line.trim_end_matches(['\r', '\n']).chars());
+            self.buffer.push_back('\n');
+        }
+        Ok(())
+    }
+
+    fn get(&mut self, input: &mut impl BufRead) -> io::Result<char> {
+        self.fill(input)?;
+        Ok(self.buffer.pop_front().unwrap())
+    }
+
+    /// Rest of the current line without the surrounding whitespace,
+    /// blank lines are skipped.
+    fn get_line(&mut self, input: &mut impl BufRead) -> io::Result<String> {
+        loop {
+            self.fill(input)?;
+            let line: String = self.buffer.drain(..).collect();
             let line = line.trim();
-            for c in line.chars() {
-                self.buffer.push_back(c);
+            if !line.is_empty() {
+                return Ok(line.to_string());
             }
         }
     }
 }
 
+fn parse_num<T>(line: &str) -> io::Result<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    T::from_str(line).map_err(|error| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Couldn't parse a number {:?}: {}", line, error),
+        )
+    })
+}
+
+/// # SystemDisplay
+/// Reads the input from stdin and prints to stdout.
+pub struct SystemDisplay {
+    buffer: LineBuffer,
+}
+
+#[allow(clippy::new_without_default)]
+impl SystemDisplay {
+    pub fn new() -> Self {
+        SystemDisplay {
+            buffer: LineBuffer::new(),
+        }
+    }
+
+    fn get_parsed_num<T>(&mut self) -> io::Result<T>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        let line = self.buffer.get_line(&mut io::stdin().lock())?;
+        parse_num(&line)
+    }
+}
+
 impl Display for SystemDisplay {
-    fn print(&self, c: char) {
-        print!("{}", c);
-        let _ = io::stdout().flush();
+    fn print(&mut self, c: char) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        write!(stdout, "{}", c)?;
+        stdout.flush()
+    }
+
+    fn get(&mut self) -> io::Result<char> {
+        self.buffer.get(&mut io::stdin().lock())
+    }
+
+    fn get_line(&mut self) -> io::Result<String> {
+        self.buffer.get_line(&mut io::stdin().lock())
+    }
+
+    fn get_num(&mut self) -> io::Result<u32> {
+        self.get_parsed_num()
     }
 
-    fn get(&mut self) -> char {
-        self.check_fill_buffer();
-        self.buffer.pop_front().unwrap()
+    fn get_signed_num(&mut self) -> io::Result<i32> {
+        self.get_parsed_num()
     }
 
-    fn get_num(&mut self) -> u32 {
-        self.check_fill_buffer();
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
 
-        let line: String = self.buffer.clone().into_iter().collect();
-        self.buffer = VecDeque::new();
+/// # StreamDisplay
+/// Reads the input from [input] and writes the output to [output].
+/// See [FileDisplay] and [ScriptedDisplay] for the common setups.
+pub struct StreamDisplay<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    buffer: LineBuffer,
+}
 
-        println!("Got number: {}", line);
+impl<R: BufRead, W: Write> StreamDisplay<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        StreamDisplay {
+            input,
+            output,
+            buffer: LineBuffer::new(),
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> Display for StreamDisplay<R, W> {
+    fn print(&mut self, c: char) -> io::Result<()> {
+        write!(self.output, "{}", c)
+    }
+
+    fn get(&mut self) -> io::Result<char> {
+        self.buffer.get(&mut self.input)
+    }
+
+    fn get_line(&mut self) -> io::Result<String> {
+        self.buffer.get_line(&mut self.input)
+    }
+
+    fn get_num(&mut self) -> io::Result<u32> {
+        parse_num(&self.buffer.get_line(&mut self.input)?)
+    }
+
+    fn get_signed_num(&mut self) -> io::Result<i32> {
+        parse_num(&self.buffer.get_line(&mut self.input)?)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// # FileDisplay
+/// Reads the input from one file and writes the output to another.
+/// The output is buffered, write errors are reported by [Display::flush].
+pub type FileDisplay = StreamDisplay<BufReader<File>, BufWriter<File>>;
+
+impl FileDisplay {
+    /// Opens [input_path] for reading and creates [output_path].
+    pub fn open(input_path: &Path, output_path: &Path) -> io::Result<Self> {
+        Ok(StreamDisplay::new(
+            BufReader::new(File::open(input_path)?),
+            BufWriter::new(File::create(output_path)?),
+        ))
+    }
+}
+
+/// # ScriptedDisplay
+/// Reads the input from a string given in advance and
+/// captures the output, which can be checked through [SharedOutput].
+/// ```ignore
+/// let display = ScriptedDisplay::scripted("5\n");
+/// let output = display.output();
+/// // run the program with the display
+/// assert_eq!(output.contents(), "120");
+/// ```
+pub type ScriptedDisplay = StreamDisplay<Cursor<Vec<u8>>, SharedOutput>;
+
+impl ScriptedDisplay {
+    pub fn scripted(input: &str) -> Self {
+        StreamDisplay::new(Cursor::new(input.as_bytes().to_vec()), SharedOutput::new())
+    }
+
+    /// Handle to the captured output, it stays valid
+    /// after the display is moved into a controller.
+    pub fn output(&self) -> SharedOutput {
+        self.output.clone()
+    }
+}
+
+/// # SharedOutput
+/// Buffer for the output that can be read while
+/// another owner keeps writing to it.
+#[derive(Clone, Default)]
+pub struct SharedOutput {
+    buffer: Rc<RefCell<Vec<u8>>>,
+}
+
+impl SharedOutput {
+    pub fn new() -> Self {
+        SharedOutput::default()
+    }
+
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.buffer.borrow()).into_owned()
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// # SharedDisplay
+/// Handle to a display used by several owners, e.g. the controller
+/// and a [ConsoleDevice](crate::vm::components::devices::ConsoleDevice).
+/// They read from the same input buffer, so a line is never split
+/// between two displays reading the same stream.
+#[derive(Clone)]
+pub struct SharedDisplay {
+    display: Rc<RefCell<Box<dyn Display>>>,
+}
+
+impl SharedDisplay {
+    pub fn new(display: impl Display + 'static) -> Self {
+        SharedDisplay {
+            display: Rc::new(RefCell::new(Box::new(display))),
+        }
+    }
+}
+
+impl Display for SharedDisplay {
+    fn print(&mut self, c: char) -> io::Result<()> {
+        self.display.borrow_mut().print(c)
+    }
+
+    fn get(&mut self) -> io::Result<char> {
+        self.display.borrow_mut().get()
+    }
+
+    fn get_line(&mut self) -> io::Result<String> {
+        self.display.borrow_mut().get_line()
+    }
+
+    fn get_num(&mut self) -> io::Result<u32> {
+        self.display.borrow_mut().get_num()
+    }
+
+    fn get_signed_num(&mut self) -> io::Result<i32> {
+        self.display.borrow_mut().get_signed_num()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.display.borrow_mut().flush()
+    }
+}
+
+/// # NullDisplay
+/// Discards the output, the input is always closed.
+pub struct NullDisplay;
+
+impl NullDisplay {
+    fn closed<T>() -> io::Result<T> {
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Input is closed",
+        ))
+    }
+}
+
+impl Display for NullDisplay {
+    fn print(&mut self, _c: char) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn get(&mut self) -> io::Result<char> {
+        NullDisplay::closed()
+    }
+
+    fn get_line(&mut self) -> io::Result<String> {
+        NullDisplay::closed()
+    }
+
+    fn get_num(&mut self) -> io::Result<u32> {
+        NullDisplay::closed()
+    }
+
+    fn get_signed_num(&mut self) -> io::Result<i32> {
+        NullDisplay::closed()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vm::components::display::{Display, NullDisplay, ScriptedDisplay};
+
+    #[test]
+    fn scripted_display_reads_lines() {
+        let mut display = ScriptedDisplay::scripted("ab\n\n-12\n7");
+        let output = display.output();
+
+        assert_eq!(display.get().unwrap(), 'a');
+        assert_eq!(display.get().unwrap(), 'b');
+        assert_eq!(display.get().unwrap(), '\n');
+        assert_eq!(display.get_signed_num().unwrap(), -12);
+        assert_eq!(display.get_num().unwrap(), 7);
+        assert!(display.get().is_err());
+
+        display.print('o').unwrap();
+        display.print('k').unwrap();
+        assert_eq!(output.contents(), "ok");
+    }
 
-        u32::from_str(&line).expect("Couldn't parse a number")
+    #[test]
+    fn null_display_has_no_input() {
+        let mut display = NullDisplay;
+        display.print('x').unwrap();
+        assert!(display.get().is_err());
+        assert!(display.get_num().is_err());
     }
 }
@@ -1,61 +1,163 @@
-use std::fs::OpenOptions;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::ops::Range;
 use std::path::Path;
 
 use crate::vm::arch::ARCH_BYTES;
-use memmap::MmapMut;
+use crate::vm::error::Fault;
+use memmap::{MmapMut, MmapOptions};
+
+/// # Trait *Memory*
+/// Represents a memory device of the machine.
+/// Implementors only have to expose their contents as a byte slice,
+/// [read_byte], [write_byte], [read_word] and [write_word] are
+/// implemented on top of it and check bounds and alignment.
+pub trait Memory {
+    fn bytes(&self) -> &[u8];
+    fn mut_bytes(&mut self) -> &mut [u8];
+
+    fn size(&self) -> u32 {
+        self.bytes().len() as u32
+    }
+
+    fn read_byte(&self, addr: u32) -> Result<u8, Fault> {
+        self.bytes()
+            .get(addr as usize)
+            .copied()
+            .ok_or(Fault::OutOfBounds(addr))
+    }
+
+    fn write_byte(&mut self, addr: u32, val: u8) -> Result<(), Fault> {
+        let byte = self
+            .mut_bytes()
+            .get_mut(addr as usize)
+            .ok_or(Fault::OutOfBounds(addr))?;
+        *byte = val;
+        Ok(())
+    }
+
+    fn read_word(&self, addr: u32) -> Result<&[u8], Fault> {
+        let range = word_range(addr, self.size())?;
+        Ok(&self.bytes()[range])
+    }
+
+    fn write_word(&mut self, addr: u32, value: [u8; ARCH_BYTES as usize]) -> Result<(), Fault> {
+        let range = word_range(addr, self.size())?;
+        self.mut_bytes()[range].copy_from_slice(&value);
+        Ok(())
+    }
+}
+
+fn word_range(addr: u32, size: u32) -> Result<Range<usize>, Fault> {
+    if !addr.is_multiple_of(ARCH_BYTES) {
+        return Err(Fault::MisalignedAccess(addr));
+    }
+    let start = addr as usize;
+    let end = start + ARCH_BYTES as usize;
+    if end > size as usize {
+        return Err(Fault::OutOfBounds(addr));
+    }
+    Ok(start..end)
+}
 
 /// # Virtual Memory
 /// Simulates memory of the machine.
-/// Maps given image file to the host memory using mmap.
+/// Maps given image file to the host memory using shared mmap,
+/// so all the changes made during execution are written back
+/// to the image.
 pub struct VirtualMemory {
     base_pointer: MmapMut,
 }
 
 impl VirtualMemory {
-    pub fn new(image_path: &Path) -> Self {
-        let file = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .open(image_path)
-            .expect("Couldn't open image file");
+    pub fn new(image_path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(image_path)?;
+        let mmap_pointer = unsafe { MmapMut::map_mut(&file)? };
+
+        Ok(VirtualMemory {
+            base_pointer: mmap_pointer,
+        })
+    }
+}
+
+impl Memory for VirtualMemory {
+    fn bytes(&self) -> &[u8] {
+        &self.base_pointer
+    }
+
+    fn mut_bytes(&mut self) -> &mut [u8] {
+        &mut self.base_pointer
+    }
+}
 
-        let mmap_pointer =
-            unsafe { MmapMut::map_mut(&file).expect("Failed to initialize virtual memory") };
+/// # Copy-on-write Memory
+/// Maps given image file to the host memory using private mmap.
+/// Pages are copied on the first write, so the image file
+/// is never changed by the execution.
+pub struct CopyOnWriteMemory {
+    base_pointer: MmapMut,
+}
+
+impl CopyOnWriteMemory {
+    pub fn new(image_path: &Path) -> io::Result<Self> {
+        let file = File::open(image_path)?;
+        let mmap_pointer = unsafe { MmapOptions::new().map_copy(&file)? };
 
-        VirtualMemory {
+        Ok(CopyOnWriteMemory {
             base_pointer: mmap_pointer,
-        }
+        })
     }
+}
 
-    pub fn read_byte(&self, addr: u32) -> u8 {
-        let addr = addr as usize;
-        self.base_pointer[addr]
+impl Memory for CopyOnWriteMemory {
+    fn bytes(&self) -> &[u8] {
+        &self.base_pointer
     }
 
-    pub fn write_byte(&mut self, addr: u32, val: u8) {
-        let addr = addr as usize;
-        self.base_pointer[addr] = val;
+    fn mut_bytes(&mut self) -> &mut [u8] {
+        &mut self.base_pointer
     }
+}
 
-    pub fn read_word(&self, addr: u32) -> &[u8] {
-        assert_eq!(addr % ARCH_BYTES, 0, "The word address is not aligned");
-        let addr = addr as usize;
-        &self.base_pointer[addr..(addr + ARCH_BYTES as usize)]
+/// # Buffer Memory
+/// Anonymous memory that lives only in the host process.
+/// Useful to run images that are built in memory,
+/// e.g. in tests.
+pub struct BufferMemory {
+    buffer: Vec<u8>,
+}
+
+impl BufferMemory {
+    pub fn new(size: u32) -> Self {
+        BufferMemory {
+            buffer: vec![0; size as usize],
+        }
     }
 
-    pub fn write_word(&mut self, addr: u32, value: &[u8]) {
-        assert_eq!(addr % ARCH_BYTES, 0, "The word address is not aligned");
-        let addr = addr as usize;
-        for i in 0..ARCH_BYTES as usize {
-            self.base_pointer[addr + i] = value[i];
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        BufferMemory {
+            buffer: bytes.to_vec(),
         }
     }
 }
 
+impl Memory for BufferMemory {
+    fn bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    fn mut_bytes(&mut self) -> &mut [u8] {
+        &mut self.buffer
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::vm::components::memory::{BufferMemory, CopyOnWriteMemory, Memory};
+    use crate::vm::error::Fault;
     use crate::VirtualMemory;
     use memmap::MmapMut;
+    use std::fs;
 
     #[test]
     fn it_works() {
@@ -70,11 +172,34 @@ mod tests {
             base_pointer: mmap_pointer,
         };
 
-        assert_eq!(memory_handler.read_word(0), [18, 52, 0, 0]);
+        assert_eq!(memory_handler.read_word(0).unwrap(), [18, 52, 0, 0]);
+
+        memory_handler.write_byte(5, 12).unwrap();
+        assert_eq!(memory_handler.read_byte(5).unwrap(), 12);
+
+        memory_handler.write_byte(5, 0).unwrap();
+    }
+
+    #[test]
+    fn faults_instead_of_panics() {
+        let memory_handler = BufferMemory::new(8);
+
+        assert_eq!(memory_handler.read_word(2), Err(Fault::MisalignedAccess(2)));
+        assert_eq!(memory_handler.read_word(8), Err(Fault::OutOfBounds(8)));
+        assert_eq!(memory_handler.read_byte(8), Err(Fault::OutOfBounds(8)));
+    }
+
+    #[test]
+    fn copy_on_write_keeps_image() {
+        let image_path = std::env::temp_dir().join("toy_vmachine_copy_on_write.bin");
+        fs::write(&image_path, [1u8, 2, 3, 4]).unwrap();
 
-        memory_handler.write_byte(5, 12);
-        assert_eq!(memory_handler.read_byte(5), 12);
+        let mut memory_handler = CopyOnWriteMemory::new(&image_path).unwrap();
+        memory_handler.write_word(0, [5, 6, 7, 8]).unwrap();
+        assert_eq!(memory_handler.read_word(0).unwrap(), [5, 6, 7, 8]);
+        drop(memory_handler);
 
-        memory_handler.write_byte(5, 0);
+        assert_eq!(fs::read(&image_path).unwrap(), [1, 2, 3, 4]);
+        fs::remove_file(&image_path).unwrap();
     }
 }
@@ -1,4 +1,7 @@
 pub mod controller;
+pub mod devices;
 pub mod display;
 pub mod memory;
 pub mod state;
+pub mod syscalls;
+pub mod tracer;
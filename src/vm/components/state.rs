@@ -1,8 +1,15 @@
+use std::cell::RefCell;
+
 use crate::vm::arch::ARCH_BYTES;
-use crate::vm::components::memory::VirtualMemory;
+use crate::vm::components::controller::INTERRUPT_LINES;
+use crate::vm::components::devices::Device;
+use crate::vm::components::memory::Memory;
+use crate::vm::error::Fault;
 use crate::vm::utils::register_macro::make_registers;
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{ByteOrder, LittleEndian};
 
+// Instructions refer to the registers by these addresses. Only the
+// registers up to SP are mapped to the memory, see [Register::is_mapped].
 make_registers! {
     IP => 0 * ARCH_BYTES,
     R0 => 1 * ARCH_BYTES,
@@ -11,64 +18,377 @@ make_registers! {
     R3 => 4 * ARCH_BYTES,
     CMP => 5 * ARCH_BYTES,
     END => 6 * ARCH_BYTES,
-    SP => 7 * ARCH_BYTES
+    SP => 7 * ARCH_BYTES,
+    FLAGS => 8 * ARCH_BYTES,
+    FP => 9 * ARCH_BYTES,
+    R4 => 10 * ARCH_BYTES,
+    R5 => 11 * ARCH_BYTES,
+    R6 => 12 * ARCH_BYTES,
+    R7 => 13 * ARCH_BYTES,
+    R8 => 14 * ARCH_BYTES,
+    R9 => 15 * ARCH_BYTES,
+    R10 => 16 * ARCH_BYTES,
+    R11 => 17 * ARCH_BYTES,
+    R12 => 18 * ARCH_BYTES,
+    R13 => 19 * ARCH_BYTES,
+    R14 => 20 * ARCH_BYTES,
+    R15 => 21 * ARCH_BYTES,
+    IVT => 22 * ARCH_BYTES
+}
+
+/// # Flag
+/// Bits of the FLAGS register. Arithmetic and comparison
+/// instructions update all of them at once, see [State::set_flags].
+/// - Zero: the result is zero
+/// - Sign: the highest bit of the result is set
+/// - Carry: unsigned overflow (a borrow for subtraction)
+/// - Overflow: signed overflow
+///
+/// Interrupt is not touched by them: it is set by EI,
+/// cleared by DI and allows the pending interrupts to be taken.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Flag {
+    Zero = 0b0001,
+    Sign = 0b0010,
+    Carry = 0b0100,
+    Overflow = 0b1000,
+    Interrupt = 0b1_0000,
+}
+
+impl Flag {
+    pub fn mask(self) -> u32 {
+        self as u32
+    }
+}
+
+impl Register {
+    /// Size of the memory region at the beginning of the image
+    /// that holds the mapped registers, IP..SP.
+    pub fn header_size() -> u32 {
+        Register::SP.as_addr() + ARCH_BYTES
+    }
+
+    /// The registers added after SP are kept by the [State] instead of
+    /// the memory, since the existing images keep code and data right
+    /// after the header.
+    pub fn is_mapped(self) -> bool {
+        self.as_addr() < Register::header_size()
+    }
+
+    /// Index of an unmapped register in [State::unmapped_registers].
+    fn unmapped_index(self) -> usize {
+        ((self.as_addr() - Register::header_size()) / ARCH_BYTES) as usize
+    }
+}
+
+/// # MemoryWrite
+/// Record of a write to the memory made by an instruction,
+/// [width] is the number of written bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MemoryWrite {
+    pub address: u32,
+    pub value: u32,
+    pub width: u32,
+}
+
+/// # StackBounds
+/// Region of the memory from [base] up to [limit] (exclusive)
+/// the stack can occupy. The stack grows towards the [limit].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct StackBounds {
+    pub base: u32,
+    pub limit: u32,
+}
+
+/// A [Device] attached to the range of addresses
+/// from [base] up to [base + size] (exclusive).
+struct MappedDevice {
+    base: u32,
+    size: u32,
+    device: RefCell<Box<dyn Device>>,
 }
 
 /// # Machine State
+/// Owns the memory device of the machine. The registers up to SP are
+/// mapped to the beginning of the memory, so the memory is checked to be
+/// large enough to hold them when the state is created. The other
+/// registers are kept by the state and start with zero.
+///
+/// Instructions access the memory through [read_byte], [write_byte],
+/// [read_word] and [write_word]. While the journal is enabled,
+/// the writes made by them are recorded.
+///
+/// Stack operations fault instead of leaving the [StackBounds],
+/// see [State::default_stack_bounds] for the bounds taken from the image.
+///
+/// Accesses to the ranges of the attached devices are routed to
+/// them instead of the memory (memory-mapped I/O).
 pub struct State {
-    memory: VirtualMemory,
+    memory: Box<dyn Memory>,
+    unmapped_registers: Vec<u32>,
+    journal: Option<Vec<MemoryWrite>>,
+    stack: StackBounds,
+    devices: Vec<MappedDevice>,
 }
 
 impl State {
-    pub fn new(memory: VirtualMemory) -> Self {
-        State { memory }
+    pub fn new(memory: impl Memory + 'static) -> Result<Self, Fault> {
+        let header_size = Register::header_size();
+        if memory.size() < header_size {
+            return Err(Fault::OutOfBounds(header_size - ARCH_BYTES));
+        }
+        let mut state = State {
+            stack: StackBounds { base: 0, limit: 0 },
+            memory: Box::new(memory),
+            unmapped_registers: vec![
+                0;
+                Register::ALL
+                    .iter()
+                    .filter(|register| !register.is_mapped())
+                    .count()
+            ],
+            journal: None,
+            devices: Vec::new(),
+        };
+        state.stack = state.default_stack_bounds();
+        Ok(state)
+    }
+
+    /// Bounds of the stack laid out by the image:
+    /// - if the initial SP points into the header, the image has no stack
+    ///   and every push or pop faults;
+    /// - if the initial SP is below the initial IP, the stack lies before
+    ///   the code and can grow up to the entry point;
+    /// - otherwise it can grow up to the end of the memory, which is where
+    ///   the assembler puts it.
+    pub fn default_stack_bounds(&self) -> StackBounds {
+        let header_size = Register::header_size();
+        let sp_value = self.register_value(Register::SP);
+        let ip_value = self.register_value(Register::IP);
+        if sp_value < header_size {
+            return StackBounds {
+                base: header_size,
+                limit: header_size,
+            };
+        }
+        let limit = if sp_value < ip_value {
+            ip_value
+        } else {
+            self.memory.size()
+        };
+        StackBounds {
+            base: sp_value,
+            limit,
+        }
+    }
+
+    pub fn stack_bounds(&self) -> StackBounds {
+        self.stack
+    }
+
+    pub fn set_stack_bounds(&mut self, bounds: StackBounds) {
+        self.stack = bounds;
+    }
+
+    /// Maps [device] to the addresses starting from [base]. The range
+    /// has to be aligned and can't overlap the registers or other devices,
+    /// it may lie outside of the memory.
+    pub fn attach_device(&mut self, base: u32, device: impl Device + 'static) -> Result<(), Fault> {
+        if !base.is_multiple_of(ARCH_BYTES) {
+            return Err(Fault::MisalignedAccess(base));
+        }
+        let size = device.size();
+        let end = base.checked_add(size).ok_or(Fault::OutOfBounds(base))?;
+        let overlaps = |start: u32, length: u32| base < start + length && start < end;
+        if overlaps(0, Register::header_size())
+            || self
+                .devices
+                .iter()
+                .any(|mapped| overlaps(mapped.base, mapped.size))
+        {
+            return Err(Fault::AddressInUse(base));
+        }
+        self.devices.push(MappedDevice {
+            base,
+            size,
+            device: RefCell::new(Box::new(device)),
+        });
+        Ok(())
+    }
+
+    /// The device mapped to [addr] and the offset of [addr] in its range.
+    fn device_at(&self, addr: u32) -> Option<(&MappedDevice, u32)> {
+        self.devices.iter().find_map(|mapped| {
+            let offset = addr.wrapping_sub(mapped.base);
+            (offset < mapped.size).then_some((mapped, offset))
+        })
+    }
+
+    /// Advances the attached devices by one step and returns the
+    /// interrupt lines raised by them as a bit mask. Lines wrap
+    /// around [INTERRUPT_LINES] like in `Controller::raise_interrupt`.
+    pub fn tick_devices(&mut self) -> u32 {
+        self.devices
+            .iter()
+            .filter_map(|mapped| mapped.device.borrow_mut().tick())
+            .fold(0, |lines, line| lines | 1 << (line % INTERRUPT_LINES))
     }
 
     pub fn register_value(&self, register: Register) -> u32 {
-        self.read_word(register.as_addr())
+        if !register.is_mapped() {
+            return self.unmapped_registers[register.unmapped_index()];
+        }
+        let value = self
+            .memory
+            .read_word(register.as_addr())
+            .expect("Registers are validated in State::new");
+        LittleEndian::read_u32(value)
     }
 
     pub fn set_register_value(&mut self, register: Register, value: u32) {
-        self.write_word(register.as_addr(), value);
+        if !register.is_mapped() {
+            self.unmapped_registers[register.unmapped_index()] = value;
+            return;
+        }
+        self.memory
+            .write_word(register.as_addr(), value.to_le_bytes())
+            .expect("Registers are validated in State::new");
+    }
+
+    pub fn flag(&self, flag: Flag) -> bool {
+        self.register_value(Register::FLAGS) & flag.mask() != 0
+    }
+
+    pub fn set_flag(&mut self, flag: Flag, value: bool) {
+        let flags = self.register_value(Register::FLAGS) & !flag.mask();
+        let bit = if value { flag.mask() } else { 0 };
+        self.set_register_value(Register::FLAGS, flags | bit);
+    }
+
+    /// Sets the FLAGS register according to the [result]
+    /// of an operation and its [carry] and [overflow] bits.
+    /// The Interrupt bit is kept.
+    pub fn set_flags(&mut self, result: u32, carry: bool, overflow: bool) {
+        let mut flags = 0;
+        if result == 0 {
+            flags |= Flag::Zero.mask();
+        }
+        if (result as i32) < 0 {
+            flags |= Flag::Sign.mask();
+        }
+        if carry {
+            flags |= Flag::Carry.mask();
+        }
+        if overflow {
+            flags |= Flag::Overflow.mask();
+        }
+        flags |= self.register_value(Register::FLAGS) & Flag::Interrupt.mask();
+        self.set_register_value(Register::FLAGS, flags);
     }
 
-    pub fn pop_from_stack(&mut self, register: Register) {
-        let sp_value = self.register_value(Register::SP) - ARCH_BYTES;
-        self.set_register_value(Register::SP, sp_value);
+    pub fn pop_from_stack(&mut self, register: Register) -> Result<(), Fault> {
+        let sp_value = self.register_value(Register::SP);
+        let new_sp_value = match sp_value.checked_sub(ARCH_BYTES) {
+            Some(new_sp_value) if new_sp_value >= self.stack.base => new_sp_value,
+            _ => return Err(Fault::StackUnderflow(sp_value)),
+        };
 
-        let mut stack_value = self.memory.read_word(sp_value);
-        let stack_value = stack_value.read_u32::<LittleEndian>().unwrap();
+        let stack_value = self.read_word(new_sp_value)?;
 
+        self.set_register_value(Register::SP, new_sp_value);
         self.set_register_value(register, stack_value);
+        Ok(())
     }
 
-    pub fn push_to_stack(&mut self, register: Register) {
+    pub fn push_to_stack(&mut self, register: Register) -> Result<(), Fault> {
         let sp_value = self.register_value(Register::SP);
+        let new_sp_value = self.grow_stack(sp_value, ARCH_BYTES)?;
         let register_value = self.register_value(register);
-        self.write_word(sp_value, register_value);
-        self.set_register_value(Register::SP, sp_value + ARCH_BYTES);
+        self.write_word(sp_value, register_value)?;
+        self.set_register_value(Register::SP, new_sp_value);
+        Ok(())
+    }
+
+    /// Moves SP by [size] bytes towards the stack limit.
+    pub fn reserve_stack(&mut self, size: u32) -> Result<(), Fault> {
+        let sp_value = self.register_value(Register::SP);
+        let new_sp_value = self.grow_stack(sp_value, size)?;
+        self.set_register_value(Register::SP, new_sp_value);
+        Ok(())
+    }
+
+    fn grow_stack(&self, sp_value: u32, size: u32) -> Result<u32, Fault> {
+        match sp_value.checked_add(size) {
+            Some(new_sp_value)
+                if sp_value >= self.stack.base && new_sp_value <= self.stack.limit =>
+            {
+                Ok(new_sp_value)
+            }
+            _ => Err(Fault::StackOverflow(sp_value)),
+        }
+    }
+
+    pub fn read_byte(&self, addr: u32) -> Result<u8, Fault> {
+        match self.device_at(addr) {
+            Some((mapped, offset)) => Ok(mapped.device.borrow_mut().read(offset)? as u8),
+            None => self.memory.read_byte(addr),
+        }
+    }
+
+    pub fn write_byte(&mut self, addr: u32, value: u8) -> Result<(), Fault> {
+        match self.device_at(addr) {
+            Some((mapped, offset)) => mapped.device.borrow_mut().write(offset, value as u32)?,
+            None => self.memory.write_byte(addr, value)?,
+        }
+        self.record(addr, value as u32, 1);
+        Ok(())
+    }
+
+    pub fn read_word(&self, addr: u32) -> Result<u32, Fault> {
+        match self.device_at(addr) {
+            Some(_) if !addr.is_multiple_of(ARCH_BYTES) => Err(Fault::MisalignedAccess(addr)),
+            Some((mapped, offset)) => mapped.device.borrow_mut().read(offset),
+            None => Ok(LittleEndian::read_u32(self.memory.read_word(addr)?)),
+        }
+    }
+
+    pub fn write_word(&mut self, addr: u32, value: u32) -> Result<(), Fault> {
+        match self.device_at(addr) {
+            Some(_) if !addr.is_multiple_of(ARCH_BYTES) => {
+                return Err(Fault::MisalignedAccess(addr))
+            }
+            Some((mapped, offset)) => mapped.device.borrow_mut().write(offset, value)?,
+            None => self.memory.write_word(addr, value.to_le_bytes())?,
+        }
+        self.record(addr, value, ARCH_BYTES);
+        Ok(())
+    }
+
+    /// Starts recording memory writes, see [take_journal].
+    pub fn start_journal(&mut self) {
+        self.journal = Some(Vec::new());
     }
 
-    fn read_word(&self, addr: u32) -> u32 {
-        let mut buf = self.memory.read_word(addr);
-        buf.read_u32::<LittleEndian>()
-            .unwrap_or_else(|_| panic!("Couldn't read from address: {}", addr))
+    /// Stops recording memory writes and returns the recorded ones.
+    pub fn take_journal(&mut self) -> Vec<MemoryWrite> {
+        self.journal.take().unwrap_or_default()
     }
 
-    fn write_word(&mut self, addr: u32, value: u32) {
-        let mut wrt = vec![];
-        wrt.write_u32::<LittleEndian>(value)
-            .unwrap_or_else(|_| panic!("Couldn't write the register with address: {}", addr));
-        for (index, byte) in wrt.iter().enumerate() {
-            self.memory.write_byte(addr + index as u32, *byte);
+    fn record(&mut self, address: u32, value: u32, width: u32) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.push(MemoryWrite {
+                address,
+                value,
+                width,
+            });
         }
     }
 
-    pub fn get_memory_handler(&self) -> &VirtualMemory {
-        &self.memory
+    pub fn get_memory_handler(&self) -> &dyn Memory {
+        self.memory.as_ref()
     }
 
-    pub fn get_mut_memory_handler(&mut self) -> &mut VirtualMemory {
-        &mut self.memory
+    pub fn get_mut_memory_handler(&mut self) -> &mut dyn Memory {
+        self.memory.as_mut()
     }
 }
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::vm::components::controller::Controller;
+use crate::vm::components::devices::RngDevice;
+use crate::vm::components::state::Register;
+use crate::vm::error::Fault;
+
+/// # SyscallHandler
+/// Host service called by the SYSCALL instruction with its number in R0,
+/// see [Controller::register_syscall]. Arguments are passed in R1..R3
+/// and the result is returned in R0.
+pub type SyscallHandler = Box<dyn FnMut(&mut Controller) -> Result<(), Fault>>;
+
+pub const SYS_EXIT: u32 = 0;
+pub const SYS_WRITE: u32 = 1;
+pub const SYS_READ: u32 = 2;
+pub const SYS_TIME: u32 = 3;
+pub const SYS_RANDOM: u32 = 4;
+
+/// Handlers every controller starts with.
+pub fn standard_syscalls() -> HashMap<u32, SyscallHandler> {
+    let mut rng = RngDevice::new();
+    let random = move |controller: &mut Controller| {
+        set_result(controller, rng.next_u32());
+        Ok(())
+    };
+    HashMap::from([
+        (SYS_EXIT, Box::new(exit) as SyscallHandler),
+        (SYS_WRITE, Box::new(write)),
+        (SYS_READ, Box::new(read)),
+        (SYS_TIME, Box::new(time)),
+        (SYS_RANDOM, Box::new(random)),
+    ])
+}
+
+fn argument(controller: &Controller, register: Register) -> u32 {
+    controller.state().register_value(register)
+}
+
+fn set_result(controller: &mut Controller, value: u32) {
+    controller
+        .mut_state()
+        .set_register_value(Register::R0, value);
+}
+
+/// Stops the program with the exit code from R1.
+fn exit(controller: &mut Controller) -> Result<(), Fault> {
+    let code = argument(controller, Register::R1);
+    controller.exit(code);
+    Ok(())
+}
+
+/// Checks that the buffer at [address] of [length] bytes fits in the memory.
+fn buffer(controller: &Controller, address: u32, length: u32) -> Result<(), Fault> {
+    let size = controller.state().get_memory_handler().size();
+    match address.checked_add(length) {
+        Some(end) if end <= size => Ok(()),
+        _ => Err(Fault::OutOfBounds(address.max(size))),
+    }
+}
+
+/// Prints R2 bytes of UTF-8 text starting from the address in R1,
+/// invalid sequences are printed as U+FFFD. The buffer is read like
+/// LDB does, so it may lie in a device. Returns the number of printed bytes.
+fn write(controller: &mut Controller) -> Result<(), Fault> {
+    let address = argument(controller, Register::R1);
+    let length = argument(controller, Register::R2);
+    let bytes = (0..length)
+        .map(|offset| controller.state().read_byte(address.wrapping_add(offset)))
+        .collect::<Result<Vec<u8>, Fault>>()?;
+    for c in String::from_utf8_lossy(&bytes).chars() {
+        controller.print(c)?;
+    }
+    set_result(controller, length);
+    Ok(())
+}
+
+/// Reads the rest of the input line to the buffer at R1
+/// of R2 bytes as UTF-8, the characters that don't fit are discarded.
+/// Returns the number of stored bytes.
+fn read(controller: &mut Controller) -> Result<(), Fault> {
+    let address = argument(controller, Register::R1);
+    let capacity = argument(controller, Register::R2);
+    buffer(controller, address, capacity)?;
+    let line = controller.mut_display().get_line()?;
+    let mut length = line.len().min(capacity as usize);
+    while !line.is_char_boundary(length) {
+        length -= 1;
+    }
+    for (offset, byte) in line[..length].bytes().enumerate() {
+        controller
+            .mut_state()
+            .write_byte(address.wrapping_add(offset as u32), byte)?;
+    }
+    set_result(controller, length as u32);
+    Ok(())
+}
+
+/// Returns the seconds since the Unix epoch,
+/// the milliseconds of the current second go to R1.
+fn time(controller: &mut Controller) -> Result<(), Fault> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    set_result(controller, now.as_secs() as u32);
+    controller
+        .mut_state()
+        .set_register_value(Register::R1, now.subsec_millis());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vm::components::controller::Outcome;
+    use crate::vm::components::devices::ConsoleDevice;
+    use crate::vm::components::display::{ScriptedDisplay, SharedDisplay};
+    use crate::vm::tools::assembler::assemble;
+    use crate::{BufferMemory, Controller, Fault, Register, State, VmError};
+
+    #[test]
+    fn standard_syscalls() {
+        let image = assemble(
+            "
+                LDA R0, 2           ; read
+                LDA R1, buffer
+                LDA R2, 4
+                SYSCALL
+                MOV R0, R2
+                LDA R0, 1           ; write
+                SYSCALL
+                LDA R0, 3           ; time
+                SYSCALL
+                MOV R0, R3
+                LDA R0, 0           ; exit
+                LDA R1, 7
+                SYSCALL
+                FIN
+            buffer: .word 0, 0
+            ",
+        )
+        .unwrap();
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let display = ScriptedDisplay::scripted("hello\n");
+        let output = display.output();
+        let mut controller = Controller::builder(state).display(display).build();
+
+        assert_eq!(controller.execute(), Ok(Outcome::Exited { code: 7 }));
+        assert_eq!(output.contents(), "hell");
+        assert!(controller.state().register_value(Register::R3) > 1_000_000_000);
+    }
+
+    #[test]
+    fn custom_syscalls() {
+        let image = assemble(
+            "
+                LDA R0, 100
+                LDA R1, 20
+                SYSCALL
+                MOV R0, R2
+                LDA R0, 4           ; random
+                SYSCALL
+                LDA R0, 101
+                SYSCALL
+            ",
+        )
+        .unwrap();
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let mut controller = Controller::builder(state)
+            .syscall(100, |controller| {
+                let state = controller.mut_state();
+                let value = state.register_value(Register::R1);
+                state.set_register_value(Register::R0, value * 2 + 2);
+                Ok(())
+            })
+            .build();
+        controller.register_syscall(4, |controller| {
+            controller.mut_state().set_register_value(Register::R0, 4);
+            Ok(())
+        });
+
+        let ip = Register::header_size() + 7 * 4;
+        assert_eq!(
+            controller.execute(),
+            Err(VmError::UnknownSyscall { ip, number: 101 })
+        );
+        assert_eq!(controller.state().register_value(Register::R2), 42);
+        assert_eq!(controller.state().register_value(Register::R0), 101);
+        assert_eq!(controller.syscall(4), Ok(()));
+        assert_eq!(controller.syscall(5), Err(Fault::UnknownSyscall(5)));
+    }
+
+    #[test]
+    fn writes_utf8_buffers_in_bounds() {
+        let image = assemble(
+            "
+                LDA R0, 1
+                LDA R1, text
+                LDA R2, 7
+                SYSCALL
+                LDA R0, 1
+                LDI R2, 0xFFFFFFFF
+                SYSCALL
+            text: .string \"héllo\"
+            ",
+        )
+        .unwrap();
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let size = state.get_memory_handler().size();
+        let display = ScriptedDisplay::scripted("");
+        let output = display.output();
+        let mut controller = Controller::builder(state).display(display).build();
+
+        assert!(matches!(
+            controller.execute(),
+            Err(VmError::OutOfBounds { address, .. }) if address == size
+        ));
+        assert_eq!(output.contents(), "héllo\0");
+    }
+
+    #[test]
+    fn reads_whole_characters() {
+        let image = assemble(
+            "
+                LDA R0, 2
+                LDA R1, buffer
+                LDA R2, 2
+                SYSCALL
+                MOV R0, R3
+                LDA R0, 1
+                LDA R2, 4
+                SYSCALL
+                FIN
+            buffer: .word 0
+            ",
+        )
+        .unwrap();
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let display = ScriptedDisplay::scripted("héllo\n");
+        let output = display.output();
+        let mut controller = Controller::builder(state).display(display).build();
+
+        assert_eq!(controller.execute(), Ok(Outcome::Finished));
+        assert_eq!(controller.state().register_value(Register::R3), 1);
+        assert_eq!(output.contents(), "h\0\0\0");
+    }
+
+    #[test]
+    fn writes_from_devices() {
+        let image = assemble(
+            "
+                LDA R0, 1
+                LDI R1, 0xFFFF0000
+                LDA R2, 1
+                SYSCALL
+                FIN
+            ",
+        )
+        .unwrap();
+        let mut state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let display = SharedDisplay::new(ScriptedDisplay::scripted("x\n"));
+        state
+            .attach_device(0xFFFF_0000, ConsoleDevice::new(display.clone()))
+            .unwrap();
+        let scripted = ScriptedDisplay::scripted("");
+        let output = scripted.output();
+        let mut controller = Controller::builder(state).display(scripted).build();
+
+        assert_eq!(controller.execute(), Ok(Outcome::Finished));
+        assert_eq!(output.contents(), "x");
+    }
+}
@@ -0,0 +1,181 @@
+use std::io;
+use std::io::Write;
+
+use crate::vm::components::state::{MemoryWrite, Register};
+use crate::vm::error::VmError;
+
+/// # TraceEvent
+/// Record of a single step of the [Controller](crate::vm::components::controller::Controller).
+/// Registers are listed in the order of [Register::ALL].
+pub struct TraceEvent<'a> {
+    pub step: u64,
+    pub ip: u32,
+    pub mnemonic: &'a str,
+    pub registers_before: &'a [u32],
+    pub registers_after: &'a [u32],
+    pub memory_writes: &'a [MemoryWrite],
+    pub error: Option<&'a VmError>,
+}
+
+/// # Trait *Tracer*
+/// Consumes the events produced by the controller
+/// while it executes instructions.
+pub trait Tracer {
+    fn trace(&mut self, event: &TraceEvent) -> io::Result<()>;
+}
+
+/// # TextTracer
+/// Writes human-readable traces:
+/// ```text
+/// #0 0x0030: LDA R0, 0x40
+///   before: IP=0x00000030 R0=0x00000000 ...
+///   after:  IP=0x00000034 R0=0x00000040 ...
+///   write:  [0x00000100] <- 0x00000040 (4 bytes)
+/// ```
+pub struct TextTracer<W: Write> {
+    output: W,
+}
+
+impl<W: Write> TextTracer<W> {
+    pub fn new(output: W) -> Self {
+        TextTracer { output }
+    }
+}
+
+impl<W: Write> Tracer for TextTracer<W> {
+    fn trace(&mut self, event: &TraceEvent) -> io::Result<()> {
+        writeln!(
+            self.output,
+            "#{} {:#06x}: {}",
+            event.step, event.ip, event.mnemonic
+        )?;
+        write_registers(&mut self.output, "  before:", event.registers_before)?;
+        write_registers(&mut self.output, "  after: ", event.registers_after)?;
+        for write in event.memory_writes {
+            writeln!(
+                self.output,
+                "  write:  [{:#010x}] <- {:#010x} ({} bytes)",
+                write.address, write.value, write.width
+            )?;
+        }
+        if let Some(error) = event.error {
+            writeln!(self.output, "  fault:  {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_registers(output: &mut impl Write, title: &str, values: &[u32]) -> io::Result<()> {
+    write!(output, "{}", title)?;
+    for (register, value) in Register::ALL.iter().zip(values) {
+        write!(output, " {}={:#010x}", register, value)?;
+    }
+    writeln!(output)
+}
+
+/// # JsonTracer
+/// Writes traces as JSON Lines, one object per step:
+/// ```text
+/// {"step":0,"ip":48,"mnemonic":"LDA R0, 0x40","before":{"IP":48,...},
+///  "after":{"IP":52,...},"writes":[{"address":256,"value":64,"width":4}],"fault":null}
+/// ```
+pub struct JsonTracer<W: Write> {
+    output: W,
+}
+
+impl<W: Write> JsonTracer<W> {
+    pub fn new(output: W) -> Self {
+        JsonTracer { output }
+    }
+}
+
+impl<W: Write> Tracer for JsonTracer<W> {
+    fn trace(&mut self, event: &TraceEvent) -> io::Result<()> {
+        write!(
+            self.output,
+            "{{\"step\":{},\"ip\":{},\"mnemonic\":{},\"before\":{},\"after\":{},\"writes\":[",
+            event.step,
+            event.ip,
+            json_string(event.mnemonic),
+            json_registers(event.registers_before),
+            json_registers(event.registers_after)
+        )?;
+        for (index, write) in event.memory_writes.iter().enumerate() {
+            if index > 0 {
+                write!(self.output, ",")?;
+            }
+            write!(
+                self.output,
+                "{{\"address\":{},\"value\":{},\"width\":{}}}",
+                write.address, write.value, write.width
+            )?;
+        }
+        let fault = match event.error {
+            Some(error) => json_string(&error.to_string()),
+            None => "null".to_string(),
+        };
+        writeln!(self.output, "],\"fault\":{}}}", fault)
+    }
+}
+
+fn json_registers(values: &[u32]) -> String {
+    let fields: Vec<String> = Register::ALL
+        .iter()
+        .zip(values)
+        .map(|(register, value)| format!("\"{}\":{}", register, value))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn json_string(text: &str) -> String {
+    let mut result = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vm::components::state::MemoryWrite;
+    use crate::vm::components::tracer::{JsonTracer, TraceEvent, Tracer};
+    use crate::Register;
+
+    #[test]
+    fn writes_json_lines() {
+        let before = vec![0; Register::ALL.len()];
+        let mut after = before.clone();
+        after[0] = 4;
+        let writes = [MemoryWrite {
+            address: 32,
+            value: 7,
+            width: 1,
+        }];
+
+        let mut output = Vec::new();
+        JsonTracer::new(&mut output)
+            .trace(&TraceEvent {
+                step: 3,
+                ip: 0,
+                mnemonic: "OUT \"R0\"",
+                registers_before: &before,
+                registers_after: &after,
+                memory_writes: &writes,
+                error: None,
+            })
+            .unwrap();
+
+        let line = String::from_utf8(output).unwrap();
+        assert!(line.starts_with("{\"step\":3,\"ip\":0,\"mnemonic\":\"OUT \\\"R0\\\"\","));
+        assert!(line.contains("\"after\":{\"IP\":4,\"R0\":0,"));
+        assert!(line
+            .ends_with("\"writes\":[{\"address\":32,\"value\":7,\"width\":1}],\"fault\":null}\n"));
+    }
+}
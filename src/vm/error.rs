@@ -0,0 +1,147 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// # Fault
+/// Describes what went wrong while decoding or executing
+/// an instruction. Components that do not know which
+/// instruction is being executed (memory, registers, decoder)
+/// report a [Fault], and the controller turns it into a
+/// [VmError] by attaching the faulting IP with [Fault::at].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fault {
+    InvalidOpcode(u8),
+    InvalidRegister(u32),
+    MisalignedAccess(u32),
+    OutOfBounds(u32),
+    DivideByZero,
+    InputError(String),
+    OutputError(String),
+    InvalidCharacter(u32),
+    TraceError(String),
+    StackOverflow(u32),
+    StackUnderflow(u32),
+    AddressInUse(u32),
+    UnknownSyscall(u32),
+}
+
+impl Fault {
+    pub fn at(self, ip: u32) -> VmError {
+        match self {
+            Fault::InvalidOpcode(opcode) => VmError::InvalidOpcode { ip, opcode },
+            Fault::InvalidRegister(address) => VmError::InvalidRegister { ip, address },
+            Fault::MisalignedAccess(address) => VmError::MisalignedAccess { ip, address },
+            Fault::OutOfBounds(address) => VmError::OutOfBounds { ip, address },
+            Fault::DivideByZero => VmError::DivideByZero { ip },
+            Fault::InputError(message) => VmError::InputError { ip, message },
+            Fault::OutputError(message) => VmError::OutputError { ip, message },
+            Fault::InvalidCharacter(value) => VmError::InvalidCharacter { ip, value },
+            Fault::TraceError(message) => VmError::TraceError { ip, message },
+            Fault::StackOverflow(sp) => VmError::StackOverflow { ip, sp },
+            Fault::StackUnderflow(sp) => VmError::StackUnderflow { ip, sp },
+            Fault::AddressInUse(address) => VmError::AddressInUse { ip, address },
+            Fault::UnknownSyscall(number) => VmError::UnknownSyscall { ip, number },
+        }
+    }
+}
+
+impl From<io::Error> for Fault {
+    fn from(error: io::Error) -> Self {
+        Fault::InputError(error.to_string())
+    }
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fault::InvalidOpcode(opcode) => write!(f, "invalid opcode {:#04x}", opcode),
+            Fault::InvalidRegister(address) => {
+                write!(f, "invalid register address {:#04x}", address)
+            }
+            Fault::MisalignedAccess(address) => {
+                write!(f, "misaligned word access at {:#x}", address)
+            }
+            Fault::OutOfBounds(address) => {
+                write!(f, "memory access out of bounds at {:#x}", address)
+            }
+            Fault::DivideByZero => write!(f, "division by zero"),
+            Fault::InputError(message) => write!(f, "input error: {}", message),
+            Fault::OutputError(message) => write!(f, "output error: {}", message),
+            Fault::InvalidCharacter(value) => write!(f, "invalid character {:#x}", value),
+            Fault::TraceError(message) => write!(f, "couldn't write the trace: {}", message),
+            Fault::StackOverflow(sp) => write!(f, "stack overflow (sp = {:#x})", sp),
+            Fault::StackUnderflow(sp) => write!(f, "stack underflow (sp = {:#x})", sp),
+            Fault::AddressInUse(address) => write!(f, "address {:#x} is already in use", address),
+            Fault::UnknownSyscall(number) => write!(f, "unknown syscall {}", number),
+        }
+    }
+}
+
+impl Error for Fault {}
+
+/// # VmError
+/// Error returned by the [Controller](crate::vm::components::controller::Controller)
+/// when a guest program faults. Every variant carries the value
+/// of the IP register of the faulting instruction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VmError {
+    InvalidOpcode { ip: u32, opcode: u8 },
+    InvalidRegister { ip: u32, address: u32 },
+    MisalignedAccess { ip: u32, address: u32 },
+    OutOfBounds { ip: u32, address: u32 },
+    DivideByZero { ip: u32 },
+    InputError { ip: u32, message: String },
+    OutputError { ip: u32, message: String },
+    InvalidCharacter { ip: u32, value: u32 },
+    TraceError { ip: u32, message: String },
+    StackOverflow { ip: u32, sp: u32 },
+    StackUnderflow { ip: u32, sp: u32 },
+    AddressInUse { ip: u32, address: u32 },
+    UnknownSyscall { ip: u32, number: u32 },
+}
+
+impl VmError {
+    pub fn ip(&self) -> u32 {
+        match self {
+            VmError::InvalidOpcode { ip, .. }
+            | VmError::InvalidRegister { ip, .. }
+            | VmError::MisalignedAccess { ip, .. }
+            | VmError::OutOfBounds { ip, .. }
+            | VmError::DivideByZero { ip }
+            | VmError::InputError { ip, .. }
+            | VmError::OutputError { ip, .. }
+            | VmError::InvalidCharacter { ip, .. }
+            | VmError::TraceError { ip, .. }
+            | VmError::StackOverflow { ip, .. }
+            | VmError::StackUnderflow { ip, .. }
+            | VmError::AddressInUse { ip, .. }
+            | VmError::UnknownSyscall { ip, .. } => *ip,
+        }
+    }
+
+    pub fn fault(&self) -> Fault {
+        match self {
+            VmError::InvalidOpcode { opcode, .. } => Fault::InvalidOpcode(*opcode),
+            VmError::InvalidRegister { address, .. } => Fault::InvalidRegister(*address),
+            VmError::MisalignedAccess { address, .. } => Fault::MisalignedAccess(*address),
+            VmError::OutOfBounds { address, .. } => Fault::OutOfBounds(*address),
+            VmError::DivideByZero { .. } => Fault::DivideByZero,
+            VmError::InputError { message, .. } => Fault::InputError(message.clone()),
+            VmError::OutputError { message, .. } => Fault::OutputError(message.clone()),
+            VmError::InvalidCharacter { value, .. } => Fault::InvalidCharacter(*value),
+            VmError::TraceError { message, .. } => Fault::TraceError(message.clone()),
+            VmError::StackOverflow { sp, .. } => Fault::StackOverflow(*sp),
+            VmError::StackUnderflow { sp, .. } => Fault::StackUnderflow(*sp),
+            VmError::AddressInUse { address, .. } => Fault::AddressInUse(*address),
+            VmError::UnknownSyscall { number, .. } => Fault::UnknownSyscall(*number),
+        }
+    }
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (ip = {:#x})", self.fault(), self.ip())
+    }
+}
+
+impl Error for VmError {}
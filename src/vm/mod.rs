@@ -1,3 +1,5 @@
-mod arch;
-pub(crate) mod components;
+pub mod arch;
+pub mod components;
+pub mod error;
+pub mod tools;
 pub(crate) mod utils;
@@ -0,0 +1,765 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+use crate::vm::arch::ARCH_BYTES;
+use crate::vm::components::state::Register;
+use crate::vm::tools::parse_number;
+
+/// Size of the stack appended to the image
+/// when the source doesn't reserve one with `.stack`.
+pub const DEFAULT_STACK_SIZE: u32 = 256;
+
+/// Largest image the assembler produces, including the stack.
+pub const MAX_IMAGE_SIZE: u32 = 0x0100_0000;
+
+/// Label of the first instruction to execute.
+/// If the label is not defined, execution starts right after the registers.
+pub const ENTRY_LABEL: &str = "start";
+
+/// # Operands
+/// Describes how the operands of an instruction
+/// are encoded in bytes 2..4 of the instruction word.
+#[derive(Copy, Clone, Debug)]
+enum Operands {
+    /// No operands
+    None,
+    /// Register in the 2nd byte
+    Register,
+    /// Registers in the 2nd and 3rd bytes
+    TwoRegisters,
+    /// Registers in the 2nd, 3rd and 4th bytes
+    ThreeRegisters,
+    /// i16 offset from the instruction in the 2nd and 3rd bytes
+    Offset,
+    /// Register in the 2nd byte, i16 offset from the instruction
+    /// in the 3rd and 4th bytes
+    RegisterOffset,
+    /// Register in the 2nd byte, u16 value in the 3rd and 4th bytes
+    RegisterImmediate,
+    /// Register in the 2nd byte, i16 value in the 3rd and 4th bytes
+    RegisterSignedImmediate,
+    /// u16 value in the 2nd and 3rd bytes
+    Immediate,
+    /// Registers in the 2nd and 3rd bytes, i8 value in the 4th byte.
+    /// The value can be omitted and defaults to zero.
+    TwoRegistersByte,
+}
+
+/// Mnemonics, codes and operands of the instructions,
+/// see `docs/instructions.md`.
+const INSTRUCTIONS: &[(&str, u8, Operands)] = &[
+    ("ADD", 0x01, Operands::ThreeRegisters),
+    ("SUB", 0x02, Operands::ThreeRegisters),
+    ("MUL", 0x03, Operands::ThreeRegisters),
+    ("DIV", 0x04, Operands::ThreeRegisters),
+    ("JMP", 0x05, Operands::Offset),
+    ("LD", 0x06, Operands::RegisterOffset),
+    ("FIN", 0x07, Operands::None),
+    ("OUT", 0x08, Operands::Register),
+    ("EQ", 0x09, Operands::TwoRegisters),
+    ("L", 0x0A, Operands::TwoRegisters),
+    ("LE", 0x0B, Operands::TwoRegisters),
+    ("LDA", 0x0C, Operands::RegisterImmediate),
+    ("INP", 0x0D, Operands::Register),
+    ("JCMP", 0x0E, Operands::Offset),
+    ("JNCMP", 0x0F, Operands::Offset),
+    ("OUTR", 0x10, Operands::Register),
+    ("SKIP", 0x11, Operands::None),
+    ("OUTN", 0x12, Operands::Register),
+    ("MOV", 0x13, Operands::TwoRegisters),
+    ("INPN", 0x14, Operands::Register),
+    ("PUSH", 0x15, Operands::Register),
+    ("POP", 0x16, Operands::Register),
+    ("CALL", 0x17, Operands::Offset),
+    ("RET", 0x18, Operands::None),
+    ("STB", 0x19, Operands::TwoRegistersByte),
+    ("STW", 0x1A, Operands::TwoRegistersByte),
+    ("LDB", 0x1B, Operands::TwoRegistersByte),
+    ("LDW", 0x1C, Operands::TwoRegistersByte),
+    ("DEREF", 0x1D, Operands::TwoRegistersByte),
+    ("AND", 0x1E, Operands::ThreeRegisters),
+    ("OR", 0x1F, Operands::ThreeRegisters),
+    ("XOR", 0x20, Operands::ThreeRegisters),
+    ("NOT", 0x21, Operands::TwoRegisters),
+    ("SHL", 0x22, Operands::ThreeRegisters),
+    ("SHR", 0x23, Operands::ThreeRegisters),
+    ("SAR", 0x24, Operands::ThreeRegisters),
+    ("IDIV", 0x25, Operands::ThreeRegisters),
+    ("IMOD", 0x26, Operands::ThreeRegisters),
+    ("ILT", 0x27, Operands::TwoRegisters),
+    ("ILE", 0x28, Operands::TwoRegisters),
+    ("IGT", 0x29, Operands::TwoRegisters),
+    ("IGE", 0x2A, Operands::TwoRegisters),
+    ("OUTNI", 0x2B, Operands::Register),
+    ("INPNI", 0x2C, Operands::Register),
+    ("JZ", 0x2D, Operands::Offset),
+    ("JNZ", 0x2E, Operands::Offset),
+    ("JC", 0x2F, Operands::Offset),
+    ("JNC", 0x30, Operands::Offset),
+    ("JO", 0x31, Operands::Offset),
+    ("JNO", 0x32, Operands::Offset),
+    ("JS", 0x33, Operands::Offset),
+    ("JNS", 0x34, Operands::Offset),
+    ("MOD", 0x35, Operands::ThreeRegisters),
+    ("MULH", 0x36, Operands::ThreeRegisters),
+    ("ADDI", 0x37, Operands::RegisterSignedImmediate),
+    ("SUBI", 0x38, Operands::RegisterSignedImmediate),
+    ("CMPI", 0x39, Operands::RegisterSignedImmediate),
+    ("LUI", 0x3A, Operands::RegisterImmediate),
+    ("LLI", 0x3B, Operands::RegisterImmediate),
+    ("JMPR", 0x3C, Operands::Register),
+    ("CALLR", 0x3D, Operands::Register),
+    ("LDS", 0x3E, Operands::RegisterSignedImmediate),
+    ("STS", 0x3F, Operands::RegisterSignedImmediate),
+    ("LDF", 0x40, Operands::RegisterSignedImmediate),
+    ("STF", 0x41, Operands::RegisterSignedImmediate),
+    ("ENTER", 0x42, Operands::Immediate),
+    ("LEAVE", 0x43, Operands::None),
+    ("EI", 0x44, Operands::None),
+    ("DI", 0x45, Operands::None),
+    ("IRET", 0x46, Operands::None),
+    ("SYSCALL", 0x47, Operands::None),
+];
+
+/// Pseudo-instruction that loads a 32-bit value, it is
+/// assembled into `LUI` and `LLI` with the halves of the value.
+const LOAD_IMMEDIATE: &str = "LDI";
+
+/// # AsmError
+/// Error in the assembly source, [line] is 1-based.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AsmError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for AsmError {}
+
+/// A single item of the program that occupies memory.
+enum Item {
+    Instruction {
+        opcode: u8,
+        operands: Operands,
+        arguments: Vec<String>,
+    },
+    LoadImmediate(Vec<String>),
+    Bytes(Vec<u8>),
+    Words(Vec<String>),
+    Zero(u32),
+    Stack(u32),
+}
+
+impl Item {
+    /// Size of the item, [None] if it doesn't fit into the address space.
+    fn size(&self) -> Option<u32> {
+        match self {
+            Item::Instruction { .. } => Some(ARCH_BYTES),
+            Item::LoadImmediate(_) => Some(2 * ARCH_BYTES),
+            Item::Bytes(bytes) => align(u32::try_from(bytes.len()).ok()?),
+            Item::Words(words) => u32::try_from(words.len()).ok()?.checked_mul(ARCH_BYTES),
+            Item::Zero(size) | Item::Stack(size) => align(*size),
+        }
+    }
+}
+
+struct Line {
+    number: usize,
+    address: u32,
+    size: u32,
+    item: Item,
+}
+
+/// # Assembler
+/// Translates the textual assembly language into an image.
+///
+/// Each line holds an optional `label:`, followed by an
+/// instruction or a directive. Comments start with `;`.
+/// ```text
+/// start:
+///     LDA R0, message
+///     OUT R0
+///     FIN
+/// message:
+///     .string "HELLO WORLD"
+/// ```
+///
+/// Directives:
+/// - `.string "text"` - null-terminated string
+/// - `.word value, ...` - 32-bit words, labels are replaced with their addresses
+/// - `.zero size` - zeroed bytes
+/// - `.stack size` - zeroed bytes used as the stack, SP points to the first of them.
+///   It has to be the last item, since the stack is bounded by the end of the image
+///
+/// Data directives are padded to the word size, so instructions
+/// that follow them stay aligned.
+///
+/// The image starts with the registers. IP points to the
+/// [ENTRY_LABEL] label, SP points to the stack. If no stack is
+/// reserved, [DEFAULT_STACK_SIZE] bytes are appended to the image.
+/// Images larger than [MAX_IMAGE_SIZE] are rejected.
+pub struct Assembler {
+    lines: Vec<Line>,
+    labels: HashMap<String, u32>,
+    address: u32,
+    stack: Option<u32>,
+}
+
+#[allow(clippy::new_without_default)]
+impl Assembler {
+    pub fn new() -> Self {
+        Assembler {
+            lines: Vec::new(),
+            labels: HashMap::new(),
+            address: Register::header_size(),
+            stack: None,
+        }
+    }
+
+    pub fn assemble(mut self, source: &str) -> Result<Vec<u8>, AsmError> {
+        for (index, text) in source.lines().enumerate() {
+            self.parse_line(index + 1, text)
+                .map_err(|message| AsmError {
+                    line: index + 1,
+                    message,
+                })?;
+        }
+        if self.stack.is_none() {
+            let line = source.lines().count().max(1);
+            self.push_item(line, Item::Stack(DEFAULT_STACK_SIZE))
+                .map_err(|message| AsmError { line, message })?;
+        }
+        self.emit()
+    }
+
+    fn parse_line(&mut self, number: usize, text: &str) -> Result<(), String> {
+        let mut text = strip_comment(text).trim();
+
+        if let Some((label, rest)) = text.split_once(':') {
+            if is_identifier(label.trim()) {
+                self.define_label(label.trim())?;
+                text = rest.trim();
+            }
+        }
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let (name, rest) = match text.split_once(char::is_whitespace) {
+            Some((name, rest)) => (name, rest.trim()),
+            None => (text, ""),
+        };
+        let item = match name.strip_prefix('.') {
+            Some(directive) => self.parse_directive(directive, rest)?,
+            None => parse_instruction(name, rest)?,
+        };
+        self.push_item(number, item)
+    }
+
+    fn parse_directive(&mut self, directive: &str, rest: &str) -> Result<Item, String> {
+        match directive {
+            "string" => {
+                let mut bytes = parse_string(rest)?;
+                bytes.push(0);
+                Ok(Item::Bytes(bytes))
+            }
+            "word" => Ok(Item::Words(split_arguments(rest))),
+            "zero" => Ok(Item::Zero(parse_size(rest)?)),
+            "stack" => Ok(Item::Stack(parse_size(rest)?)),
+            _ => Err(format!("Unknown directive .{}", directive)),
+        }
+    }
+
+    fn define_label(&mut self, label: &str) -> Result<(), String> {
+        if Register::from_name(label).is_some() {
+            return Err(format!("Label {:?} is a register name", label));
+        }
+        if self
+            .labels
+            .insert(label.to_string(), self.address)
+            .is_some()
+        {
+            return Err(format!("Label {:?} is defined twice", label));
+        }
+        Ok(())
+    }
+
+    fn push_item(&mut self, number: usize, item: Item) -> Result<(), String> {
+        if self.stack.is_some() {
+            return Err("The stack has to be the last item of the image".to_string());
+        }
+        let address = self.address;
+        let size = item.size();
+        self.address = size
+            .and_then(|size| address.checked_add(size))
+            .filter(|&end| end <= MAX_IMAGE_SIZE)
+            .ok_or_else(|| {
+                format!(
+                    "The image is too large, the limit is {:#x} bytes",
+                    MAX_IMAGE_SIZE
+                )
+            })?;
+        if let Item::Stack(_) = item {
+            self.stack.get_or_insert(address);
+        }
+        self.lines.push(Line {
+            number,
+            address,
+            size: self.address - address,
+            item,
+        });
+        Ok(())
+    }
+
+    fn emit(&self) -> Result<Vec<u8>, AsmError> {
+        let mut image = Vec::with_capacity(self.address as usize);
+        image.resize(Register::header_size() as usize, 0);
+        let entry = self
+            .labels
+            .get(ENTRY_LABEL)
+            .copied()
+            .unwrap_or(Register::header_size());
+        let sp_value = self.stack.unwrap_or(self.address);
+        write_word(&mut image, Register::IP.as_addr(), entry);
+        write_word(&mut image, Register::SP.as_addr(), sp_value);
+
+        for line in &self.lines {
+            let error = |message| AsmError {
+                line: line.number,
+                message,
+            };
+            match &line.item {
+                Item::Instruction {
+                    opcode,
+                    operands,
+                    arguments,
+                } => {
+                    let code = self
+                        .encode(*opcode, *operands, arguments, line.address)
+                        .map_err(error)?;
+                    image.extend_from_slice(&code);
+                }
+                Item::LoadImmediate(arguments) => {
+                    for code in self
+                        .encode_load_immediate(arguments, line.address)
+                        .map_err(error)?
+                    {
+                        image.extend_from_slice(&code);
+                    }
+                }
+                Item::Words(words) => {
+                    for word in words {
+                        let value = self.value(word).map_err(error)?;
+                        image.extend_from_slice(&value.to_le_bytes());
+                    }
+                }
+                Item::Bytes(bytes) => image.extend_from_slice(bytes),
+                Item::Zero(_) | Item::Stack(_) => {}
+            }
+            image.resize((line.address + line.size) as usize, 0);
+        }
+        Ok(image)
+    }
+
+    fn encode(
+        &self,
+        opcode: u8,
+        operands: Operands,
+        arguments: &[String],
+        address: u32,
+    ) -> Result<[u8; 4], String> {
+        let mut code = [opcode, 0, 0, 0];
+        match (operands, arguments) {
+            (Operands::None, []) => {}
+            (Operands::Register, [register]) => {
+                code[1] = parse_register(register)?;
+            }
+            (Operands::TwoRegisters, [first, second]) => {
+                code[1] = parse_register(first)?;
+                code[2] = parse_register(second)?;
+            }
+            (Operands::ThreeRegisters, [first, second, third]) => {
+                code[1] = parse_register(first)?;
+                code[2] = parse_register(second)?;
+                code[3] = parse_register(third)?;
+            }
+            (Operands::Offset, [target]) => {
+                let offset = self.offset(target, address)?;
+                code[1..=2].copy_from_slice(&offset.to_le_bytes());
+            }
+            (Operands::RegisterOffset, [register, target]) => {
+                code[1] = parse_register(register)?;
+                let offset = self.offset(target, address)?;
+                code[2..=3].copy_from_slice(&offset.to_le_bytes());
+            }
+            (Operands::RegisterImmediate, [register, value]) => {
+                code[1] = parse_register(register)?;
+                let value = self.value(value)?;
+                let value = u16::try_from(value)
+                    .map_err(|_| format!("Value {:#x} doesn't fit into 16 bits", value))?;
+                code[2..=3].copy_from_slice(&value.to_le_bytes());
+            }
+            (Operands::RegisterSignedImmediate, [register, value]) => {
+                code[1] = parse_register(register)?;
+                let value = self.value(value)? as i32;
+                let value = i16::try_from(value)
+                    .map_err(|_| format!("Value {} doesn't fit into 16 bits", value))?;
+                code[2..=3].copy_from_slice(&value.to_le_bytes());
+            }
+            (Operands::Immediate, [value]) => {
+                let value = self.value(value)?;
+                let value = u16::try_from(value)
+                    .map_err(|_| format!("Value {:#x} doesn't fit into 16 bits", value))?;
+                code[1..=2].copy_from_slice(&value.to_le_bytes());
+            }
+            (Operands::TwoRegistersByte, [first, second, rest @ ..]) if rest.len() <= 1 => {
+                code[1] = parse_register(first)?;
+                code[2] = parse_register(second)?;
+                if let [value] = rest {
+                    code[3] = parse_byte(value)? as u8;
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "Wrong operands {:?}, expected {:?}",
+                    arguments, operands
+                ))
+            }
+        }
+        Ok(code)
+    }
+
+    /// Encodes [LOAD_IMMEDIATE] as `LUI` and `LLI`.
+    fn encode_load_immediate(
+        &self,
+        arguments: &[String],
+        address: u32,
+    ) -> Result<[[u8; 4]; 2], String> {
+        let [register, value] = arguments else {
+            return Err(format!(
+                "Wrong operands {:?}, expected a register and a value",
+                arguments
+            ));
+        };
+        let value = self.value(value)?;
+        let upper = [register.clone(), (value >> 16).to_string()];
+        let lower = [register.clone(), (value & 0xFFFF).to_string()];
+        Ok([
+            self.encode(opcode("LUI"), Operands::RegisterImmediate, &upper, address)?,
+            self.encode(
+                opcode("LLI"),
+                Operands::RegisterImmediate,
+                &lower,
+                address + ARCH_BYTES,
+            )?,
+        ])
+    }
+
+    /// Value of a number literal, a character literal or a label address.
+    fn value(&self, argument: &str) -> Result<u32, String> {
+        if let Some(&address) = self.labels.get(argument) {
+            return Ok(address);
+        }
+        parse_literal(argument).ok_or_else(|| format!("Unknown label or value {:?}", argument))
+    }
+
+    /// Offset from the instruction at [address] to a label,
+    /// or a literal offset.
+    fn offset(&self, argument: &str, address: u32) -> Result<i16, String> {
+        let offset = match self.labels.get(argument) {
+            Some(&target) => target as i64 - address as i64,
+            None => parse_literal(argument)
+                .map(|value| value as i32 as i64)
+                .ok_or_else(|| format!("Unknown label or offset {:?}", argument))?,
+        };
+        i16::try_from(offset).map_err(|_| format!("Offset {} doesn't fit into 16 bits", offset))
+    }
+}
+
+fn parse_instruction(name: &str, rest: &str) -> Result<Item, String> {
+    let name = name.to_ascii_uppercase();
+    if name == LOAD_IMMEDIATE {
+        return Ok(Item::LoadImmediate(split_arguments(rest)));
+    }
+    let (_, opcode, operands) = INSTRUCTIONS
+        .iter()
+        .find(|(mnemonic, _, _)| *mnemonic == name)
+        .ok_or_else(|| format!("Unknown instruction {}", name))?;
+    Ok(Item::Instruction {
+        opcode: *opcode,
+        operands: *operands,
+        arguments: split_arguments(rest),
+    })
+}
+
+fn opcode(name: &str) -> u8 {
+    INSTRUCTIONS
+        .iter()
+        .find(|(mnemonic, _, _)| *mnemonic == name)
+        .map(|(_, opcode, _)| *opcode)
+        .expect("The instruction is in the table")
+}
+
+fn parse_register(argument: &str) -> Result<u8, String> {
+    Register::from_name(argument)
+        .map(|register| register.as_addr() as u8)
+        .ok_or_else(|| format!("Unknown register {:?}", argument))
+}
+
+/// Parses numbers (`42`, `-8`, `0x40`) and characters (`'a'`, `'\n'`).
+fn parse_literal(argument: &str) -> Option<u32> {
+    if let Some(character) = argument
+        .strip_prefix('\'')
+        .and_then(|rest| rest.strip_suffix('\''))
+    {
+        return match unescape(character).ok()?.as_slice() {
+            [byte] => Some(*byte as u32),
+            _ => None,
+        };
+    }
+    match argument.strip_prefix('-') {
+        Some(number) => parse_number(number)
+            .filter(|&value| value <= i32::MAX as u32 + 1)
+            .map(|value| value.wrapping_neg()),
+        None => parse_number(argument),
+    }
+}
+
+fn parse_byte(argument: &str) -> Result<i8, String> {
+    parse_literal(argument)
+        .and_then(|value| i8::try_from(value as i32).ok())
+        .ok_or_else(|| format!("Value {:?} doesn't fit into i8", argument))
+}
+
+fn parse_size(argument: &str) -> Result<u32, String> {
+    parse_number(argument).ok_or_else(|| format!("Invalid size {:?}", argument))
+}
+
+fn parse_string(argument: &str) -> Result<Vec<u8>, String> {
+    let text = argument
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .ok_or_else(|| format!("Expected a quoted string, got {:?}", argument))?;
+    unescape(text)
+}
+
+fn unescape(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' => match chars.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('0') => '\0',
+                Some(c @ ('\\' | '"' | '\'')) => c,
+                other => return Err(format!("Invalid escape sequence \\{:?}", other)),
+            },
+            c => c,
+        };
+        let mut buffer = [0; 4];
+        bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+    }
+    Ok(bytes)
+}
+
+/// Characters of [text] that are outside of string and character literals.
+fn unquoted_chars(text: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut quote = None;
+    let mut escaped = false;
+    text.char_indices().filter(move |&(_, c)| match quote {
+        None => {
+            if c == '"' || c == '\'' {
+                quote = Some(c);
+            }
+            quote.is_none()
+        }
+        Some(_) if escaped => {
+            escaped = false;
+            false
+        }
+        Some(_) if c == '\\' => {
+            escaped = true;
+            false
+        }
+        Some(closing) => {
+            if c == closing {
+                quote = None;
+            }
+            false
+        }
+    })
+}
+
+/// Splits the operands on the commas outside of literals, so `','` is one operand.
+fn split_arguments(text: &str) -> Vec<String> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let mut arguments = Vec::new();
+    let mut start = 0;
+    for (index, _) in unquoted_chars(text).filter(|&(_, c)| c == ',') {
+        arguments.push(text[start..index].trim().to_string());
+        start = index + 1;
+    }
+    arguments.push(text[start..].trim().to_string());
+    arguments
+}
+
+fn strip_comment(text: &str) -> &str {
+    match unquoted_chars(text).find(|&(_, c)| c == ';') {
+        Some((index, _)) => &text[..index],
+        None => text,
+    }
+}
+
+fn is_identifier(text: &str) -> bool {
+    !text.is_empty()
+        && !text.starts_with(|c: char| c.is_ascii_digit())
+        && text.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn align(size: u32) -> Option<u32> {
+    size.div_ceil(ARCH_BYTES).checked_mul(ARCH_BYTES)
+}
+
+fn write_word(image: &mut [u8], addr: u32, value: u32) {
+    let addr = addr as usize;
+    image[addr..addr + ARCH_BYTES as usize].copy_from_slice(&value.to_le_bytes());
+}
+
+/// Assembles [source] into an image, see [Assembler].
+pub fn assemble(source: &str) -> Result<Vec<u8>, AsmError> {
+    Assembler::new().assemble(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vm::tools::assembler::{assemble, AsmError, INSTRUCTIONS, MAX_IMAGE_SIZE};
+    use crate::{decode, BufferMemory, Controller, Register, State};
+
+    #[test]
+    fn mnemonics_match_decoder() {
+        for (mnemonic, opcode, _) in INSTRUCTIONS {
+            let instruction = decode(&[*opcode, 0x04, 0x04, 0x04]).unwrap();
+            let text = instruction.to_string();
+            assert_eq!(text.split(' ').next(), Some(*mnemonic));
+        }
+    }
+
+    #[test]
+    fn computes_offsets_and_header() {
+        let image = assemble(
+            "
+            message: .string \"HI\"
+            start:
+                LDA R0, message   ; absolute address
+            loop: JMP loop
+                CALL start
+            ",
+        )
+        .unwrap();
+
+        let base = Register::header_size() as usize;
+        assert_eq!(&image[base..base + 4], b"HI\0\0");
+        assert_eq!(&image[base + 4..base + 8], [0x0C, 0x04, base as u8, 0x00]);
+        assert_eq!(&image[base + 8..base + 12], [0x05, 0x00, 0x00, 0x00]);
+        assert_eq!(&image[base + 12..base + 16], [0x17, 0xF8, 0xFF, 0x00]);
+        assert_eq!(image.len(), base + 16 + 256);
+
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        assert_eq!(state.register_value(Register::IP), base as u32 + 4);
+        assert_eq!(state.register_value(Register::SP), base as u32 + 16);
+    }
+
+    #[test]
+    fn runs_assembled_program() {
+        let image = assemble(
+            "
+                LDA R0, 6
+                LDA R1, 7
+                CALL multiply
+                FIN
+            multiply:
+                MUL R0, R1, R0
+                RET
+                .stack 16
+            ",
+        )
+        .unwrap();
+
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let mut controller = Controller::new(state);
+        controller.execute().unwrap();
+        assert_eq!(controller.state().register_value(Register::R0), 42);
+    }
+
+    #[test]
+    fn expands_load_immediate() {
+        let image = assemble("LDI R1, 0x12345678").unwrap();
+        let base = Register::header_size() as usize;
+        assert_eq!(&image[base..base + 4], [0x3A, 0x08, 0x34, 0x12]);
+        assert_eq!(&image[base + 4..base + 8], [0x3B, 0x08, 0x78, 0x56]);
+        assert_eq!(assemble("FIN\nLDI R1").unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn reports_errors_with_lines() {
+        assert_eq!(
+            assemble("FIN\nJMP nowhere"),
+            Err(AsmError {
+                line: 2,
+                message: "Unknown label or offset \"nowhere\"".to_string(),
+            })
+        );
+        assert_eq!(assemble("ADD R0, R1").unwrap_err().line, 1);
+        assert_eq!(assemble("MOV R0, R16").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn splits_operands_outside_literals() {
+        let image = assemble(
+            "
+                LDA R0, ','
+                LDA R1, '\\\\'   ; backslash
+                .word ';', ','
+                .string \"a, b; c\"
+            ",
+        )
+        .unwrap();
+        let base = Register::header_size() as usize;
+        assert_eq!(&image[base..base + 4], [0x0C, 0x04, b',', 0x00]);
+        assert_eq!(&image[base + 4..base + 8], [0x0C, 0x08, b'\\', 0x00]);
+        assert_eq!(&image[base + 8..base + 16], [b';', 0, 0, 0, b',', 0, 0, 0]);
+        assert_eq!(&image[base + 16..base + 24], b"a, b; c\0");
+    }
+
+    #[test]
+    fn rejects_too_large_images() {
+        let message = format!(
+            "The image is too large, the limit is {:#x} bytes",
+            MAX_IMAGE_SIZE
+        );
+        assert_eq!(
+            assemble("FIN\n.zero 0xFFFFFFFF"),
+            Err(AsmError {
+                line: 2,
+                message: message.clone(),
+            })
+        );
+        assert_eq!(assemble(".stack 0xFFFFFFF0").unwrap_err().message, message);
+        assert_eq!(
+            assemble(&format!(".zero {}", MAX_IMAGE_SIZE))
+                .unwrap_err()
+                .line,
+            1
+        );
+        assert!(assemble(".zero 0x1000\nFIN").is_ok());
+    }
+}
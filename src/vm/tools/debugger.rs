@@ -0,0 +1,367 @@
+use std::collections::BTreeSet;
+use std::io;
+use std::io::{BufRead, Write};
+
+use crate::vm::components::controller::Controller;
+use crate::vm::components::state::Register;
+use crate::vm::tools::disassembler::disassemble_word;
+use crate::vm::tools::parse_number;
+
+const HELP: &str = "\
+Commands:
+  break <addr>          set a breakpoint on IP = addr (no argument lists them)
+  delete <addr>         remove a breakpoint
+  step [n]              execute n instructions (1 by default)
+  continue              run until a breakpoint, a fault or the end
+  regs                  print all registers
+  mem <addr> [len]      hexdump len bytes of memory (64 by default)
+  set <reg> <value>     change the value of a register
+  poke <addr> <byte>..  change bytes of memory
+  help                  print this message
+  quit                  leave the debugger";
+
+const HEXDUMP_WIDTH: u32 = 16;
+
+/// # StdinLines
+/// Reads the commands from stdin one line at a time. Unlike a held
+/// [io::StdinLock], it leaves stdin to the program between the commands,
+/// so the program can read its input while it is debugged.
+#[derive(Default)]
+pub struct StdinLines {
+    line: Vec<u8>,
+    position: usize,
+}
+
+impl StdinLines {
+    pub fn new() -> Self {
+        StdinLines::default()
+    }
+}
+
+impl io::Read for StdinLines {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.consume(length);
+        Ok(length)
+    }
+}
+
+impl BufRead for StdinLines {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.position == self.line.len() {
+            self.line.clear();
+            self.position = 0;
+            io::stdin().lock().read_until(b'\n', &mut self.line)?;
+        }
+        Ok(&self.line[self.position..])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.position = (self.position + amount).min(self.line.len());
+    }
+}
+
+/// # Debugger
+/// Interactive debugger that wraps a [Controller].
+/// Reads commands from [input] and writes the results to [output],
+/// see [HELP] for the list of supported commands.
+pub struct Debugger<R: BufRead, W: Write> {
+    controller: Controller,
+    breakpoints: BTreeSet<u32>,
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    pub fn new(controller: Controller, input: R, output: W) -> Self {
+        Debugger {
+            controller,
+            breakpoints: BTreeSet::new(),
+            input,
+            output,
+        }
+    }
+
+    pub fn controller(&self) -> &Controller {
+        &self.controller
+    }
+
+    pub fn run(&mut self) -> io::Result<()> {
+        self.print_location()?;
+        loop {
+            write!(self.output, "(vm) ")?;
+            self.output.flush()?;
+
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let Some((&command, arguments)) = words.split_first() else {
+                continue;
+            };
+
+            match command {
+                "b" | "break" => self.set_breakpoint(arguments)?,
+                "d" | "delete" => self.delete_breakpoint(arguments)?,
+                "s" | "step" => self.step(arguments)?,
+                "c" | "continue" => self.resume()?,
+                "r" | "regs" => self.print_registers()?,
+                "x" | "mem" => self.hexdump(arguments)?,
+                "set" => self.set_register(arguments)?,
+                "poke" => self.poke(arguments)?,
+                "h" | "help" => writeln!(self.output, "{}", HELP)?,
+                "q" | "quit" => return Ok(()),
+                _ => writeln!(self.output, "Unknown command {:?}, try `help`", command)?,
+            }
+        }
+    }
+
+    fn set_breakpoint(&mut self, arguments: &[&str]) -> io::Result<()> {
+        match arguments {
+            [] => {
+                for address in &self.breakpoints {
+                    writeln!(self.output, "Breakpoint at {:#06x}", address)?;
+                }
+            }
+            [address] => match parse_number(address) {
+                Some(address) => {
+                    self.breakpoints.insert(address);
+                    writeln!(self.output, "Breakpoint set at {:#06x}", address)?;
+                }
+                None => writeln!(self.output, "Invalid address {:?}", address)?,
+            },
+            _ => writeln!(self.output, "Usage: break <addr>")?,
+        }
+        Ok(())
+    }
+
+    fn delete_breakpoint(&mut self, arguments: &[&str]) -> io::Result<()> {
+        match arguments {
+            [address] => match parse_number(address) {
+                Some(address) if self.breakpoints.remove(&address) => {
+                    writeln!(self.output, "Breakpoint at {:#06x} deleted", address)?
+                }
+                _ => writeln!(self.output, "No breakpoint at {}", address)?,
+            },
+            _ => writeln!(self.output, "Usage: delete <addr>")?,
+        }
+        Ok(())
+    }
+
+    fn step(&mut self, arguments: &[&str]) -> io::Result<()> {
+        let count = match arguments {
+            [] => 1,
+            [count] => match parse_number(count) {
+                Some(count) => count,
+                None => return writeln!(self.output, "Invalid count {:?}", count),
+            },
+            _ => return writeln!(self.output, "Usage: step [n]"),
+        };
+
+        for _ in 0..count {
+            if !self.execute_one()? {
+                return Ok(());
+            }
+        }
+        self.print_location()
+    }
+
+    fn resume(&mut self) -> io::Result<()> {
+        if !self.execute_one()? {
+            return Ok(());
+        }
+        loop {
+            let ip_value = self.controller.state().register_value(Register::IP);
+            if self.breakpoints.contains(&ip_value) {
+                writeln!(self.output, "Breakpoint hit at {:#06x}", ip_value)?;
+                return self.print_location();
+            }
+            if !self.execute_one()? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Executes one instruction and reports if the execution can go on.
+    fn execute_one(&mut self) -> io::Result<bool> {
+        if self.controller.is_finished() {
+            writeln!(self.output, "The program has finished")?;
+            return Ok(false);
+        }
+        if let Err(error) = self.controller.step() {
+            writeln!(self.output, "Machine fault: {}", error)?;
+            return Ok(false);
+        }
+        if self.controller.is_finished() {
+            writeln!(self.output, "The program has finished")?;
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn print_location(&mut self) -> io::Result<()> {
+        let ip_value = self.controller.state().register_value(Register::IP);
+        let memory = self.controller.state().get_memory_handler();
+        writeln!(self.output, "{}", disassemble_word(memory, ip_value))
+    }
+
+    fn print_registers(&mut self) -> io::Result<()> {
+        for register in Register::ALL {
+            let value = self.controller.state().register_value(*register);
+            writeln!(
+                self.output,
+                "{:<4} {:#010x} {}",
+                register.name(),
+                value,
+                value
+            )?;
+        }
+        Ok(())
+    }
+
+    fn hexdump(&mut self, arguments: &[&str]) -> io::Result<()> {
+        let (address, length) = match arguments {
+            [address] => (parse_number(address), Some(64)),
+            [address, length] => (parse_number(address), parse_number(length)),
+            _ => return writeln!(self.output, "Usage: mem <addr> [len]"),
+        };
+        let (Some(address), Some(length)) = (address, length) else {
+            return writeln!(self.output, "Invalid address or length");
+        };
+
+        let memory = self.controller.state().get_memory_handler();
+        let end = address.saturating_add(length);
+        let mut line_start = address;
+        while line_start < end {
+            let line_end = end.min(line_start.saturating_add(HEXDUMP_WIDTH));
+            let mut bytes = Vec::new();
+            for addr in line_start..line_end {
+                match memory.read_byte(addr) {
+                    Ok(byte) => bytes.push(byte),
+                    Err(_) => break,
+                }
+            }
+            if bytes.is_empty() {
+                break;
+            }
+
+            let hex: Vec<String> = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+            let text: String = bytes
+                .iter()
+                .map(|&byte| match byte {
+                    0x20..=0x7e => byte as char,
+                    _ => '.',
+                })
+                .collect();
+            writeln!(
+                self.output,
+                "{:#06x}: {:<width$}  {}",
+                line_start,
+                hex.join(" "),
+                text,
+                width = HEXDUMP_WIDTH as usize * 3 - 1
+            )?;
+            line_start = line_end;
+        }
+        Ok(())
+    }
+
+    fn set_register(&mut self, arguments: &[&str]) -> io::Result<()> {
+        let [name, value] = arguments else {
+            return writeln!(self.output, "Usage: set <reg> <value>");
+        };
+        let Some(register) = Register::from_name(name) else {
+            return writeln!(self.output, "Unknown register {:?}", name);
+        };
+        let Some(value) = parse_number(value) else {
+            return writeln!(self.output, "Invalid value {:?}", value);
+        };
+        self.controller
+            .mut_state()
+            .set_register_value(register, value);
+        Ok(())
+    }
+
+    fn poke(&mut self, arguments: &[&str]) -> io::Result<()> {
+        let Some((address, bytes)) = arguments.split_first() else {
+            return writeln!(self.output, "Usage: poke <addr> <byte>..");
+        };
+        let Some(address) = parse_number(address) else {
+            return writeln!(self.output, "Invalid address {:?}", address);
+        };
+
+        let memory = self.controller.mut_state().get_mut_memory_handler();
+        for (offset, byte) in bytes.iter().enumerate() {
+            let Some(value) = parse_number(byte).and_then(|value| u8::try_from(value).ok()) else {
+                return writeln!(self.output, "Invalid byte {:?}", byte);
+            };
+            let addr = address.wrapping_add(offset as u32);
+            if let Err(fault) = memory.write_byte(addr, value) {
+                return writeln!(self.output, "{}", fault);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vm::components::display::ScriptedDisplay;
+    use crate::vm::tools::assembler::assemble;
+    use crate::vm::tools::debugger::Debugger;
+    use crate::{BufferMemory, Controller, Register, State};
+
+    fn run_script(script: &str) -> (Controller, String) {
+        let mut image = vec![0u8; 0x30];
+        image[0] = 0x20;
+        // LDA R0, 5; LDA R1, 7; FIN
+        image[0x20..0x2C].copy_from_slice(&[0x0C, 0x04, 5, 0, 0x0C, 0x08, 7, 0, 0x07, 0, 0, 0]);
+
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let mut output = Vec::new();
+        let mut debugger = Debugger::new(Controller::new(state), script.as_bytes(), &mut output);
+        debugger.run().unwrap();
+        let Debugger { controller, .. } = debugger;
+        (controller, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn stops_on_breakpoint() {
+        let (controller, output) = run_script("break 0x24\ncontinue\nquit\n");
+        assert!(output.contains("Breakpoint hit at 0x0024"));
+        assert_eq!(controller.state().register_value(Register::R0), 5);
+        assert_eq!(controller.state().register_value(Register::R1), 0);
+    }
+
+    #[test]
+    fn modifies_registers_and_memory() {
+        let (controller, output) = run_script("set r12 0x10\npoke 0x2c 0xab\nmem 0x2c 1\nstep 3\n");
+        assert!(output.contains("0x002c: ab"));
+        assert!(output.contains("The program has finished"));
+        assert_eq!(controller.state().register_value(Register::R12), 0x10);
+        assert_eq!(controller.state().register_value(Register::R1), 7);
+    }
+
+    #[test]
+    fn steps_over_input() {
+        let image = assemble("INPN R0\nFIN").unwrap();
+        let state = State::new(BufferMemory::from_bytes(&image)).unwrap();
+        let controller = Controller::builder(state)
+            .display(ScriptedDisplay::scripted("42\n"))
+            .build();
+        let mut output = Vec::new();
+        let mut debugger = Debugger::new(controller, "step\nregs\n".as_bytes(), &mut output);
+        debugger.run().unwrap();
+
+        assert_eq!(
+            debugger.controller().state().register_value(Register::R0),
+            42
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("R0   0x0000002a 42"));
+    }
+}
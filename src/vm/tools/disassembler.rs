@@ -0,0 +1,90 @@
+use std::fmt;
+
+use crate::vm::arch::instruction::{decode, Instruction};
+use crate::vm::arch::ARCH_BYTES;
+use crate::vm::components::memory::Memory;
+use crate::vm::error::Fault;
+
+/// # DisassembledWord
+/// A word of memory together with the instruction it decodes to.
+/// Words that fail to decode (strings, numbers, padding) keep
+/// the [Fault] reported by the decoder and are displayed as data.
+pub struct DisassembledWord {
+    pub address: u32,
+    pub code: Vec<u8>,
+    pub instruction: Result<Box<dyn Instruction>, Fault>,
+}
+
+impl fmt::Display for DisassembledWord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex: Vec<String> = self
+            .code
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        write!(f, "{:#06x}:  {:<11}  ", self.address, hex.join(" "))?;
+        match &self.instruction {
+            Ok(instruction) => write!(f, "{}", instruction),
+            Err(_) => {
+                let text: String = self
+                    .code
+                    .iter()
+                    .map(|&byte| match byte {
+                        0x20..=0x7e => byte as char,
+                        _ => '.',
+                    })
+                    .collect();
+                write!(f, "<data> {}", text)
+            }
+        }
+    }
+}
+
+/// Disassembles a single word of memory at [address].
+pub fn disassemble_word(memory: &dyn Memory, address: u32) -> DisassembledWord {
+    let end = memory.size().min(address.saturating_add(ARCH_BYTES));
+    let code: Vec<u8> = (address..end)
+        .filter_map(|addr| memory.read_byte(addr).ok())
+        .collect();
+    let instruction = memory.read_word(address).and_then(decode);
+    DisassembledWord {
+        address,
+        code,
+        instruction,
+    }
+}
+
+/// Walks the memory word by word from [start] up to its end.
+pub fn disassemble(memory: &dyn Memory, start: u32) -> Vec<DisassembledWord> {
+    (start..memory.size())
+        .step_by(ARCH_BYTES as usize)
+        .map(|address| disassemble_word(memory, address))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vm::tools::disassembler::disassemble;
+    use crate::BufferMemory;
+
+    #[test]
+    fn marks_data_regions() {
+        let memory = BufferMemory::from_bytes(&[
+            0x0C, 0x04, 0x40, 0x00, 0x0E, 0xF8, 0xFF, 0x00, 0x48, 0x49, 0x00, 0x00, 0x07,
+        ]);
+        let lines: Vec<String> = disassemble(&memory, 0)
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+
+        assert_eq!(
+            lines,
+            [
+                "0x0000:  0c 04 40 00  LDA R0, 0x40",
+                "0x0004:  0e f8 ff 00  JCMP -8",
+                "0x0008:  48 49 00 00  <data> HI..",
+                "0x000c:  07           <data> .",
+            ]
+        );
+    }
+}
@@ -0,0 +1,12 @@
+pub mod assembler;
+pub mod debugger;
+pub mod disassembler;
+
+/// Parses a number written either in decimal
+/// or in hexadecimal with the `0x` prefix.
+pub fn parse_number(text: &str) -> Option<u32> {
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
@@ -4,11 +4,15 @@ macro_rules! register_instructions {
             $code_value:expr => $struct_name:ty
         ),*
     } => {
-        pub fn decode(code: &[u8]) -> Box<dyn Instruction> {
-            let instruction_code = code[0];
+        /// Decodes the instruction word in [code],
+        /// words shorter than four bytes are reported as invalid opcodes.
+        pub fn decode(code: &[u8]) -> Result<Box<dyn Instruction>, Fault> {
+            let &[instruction_code, _, _, _, ..] = code else {
+                return Err(Fault::InvalidOpcode(code.first().copied().unwrap_or_default()));
+            };
             match instruction_code {
-                $($code_value => Box::new(<$struct_name>::new(code))),*,
-                _ => panic!("Invalid instruction"),
+                $($code_value => Ok(Box::new(<$struct_name>::new(code)?))),*,
+                _ => Err(Fault::InvalidOpcode(instruction_code)),
             }
         }
     }
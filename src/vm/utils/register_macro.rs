@@ -1,10 +1,11 @@
 /// # Machine registers
 /// The following macro generates an enum with registers,
-/// as well as [as_addr] and [from_addr] functions.
+/// as well as [as_addr], [from_addr], [name] and [from_name]
+/// functions and the [ALL] list of registers.
 /// When creating a register, one specifies the address of it
 ///
 /// For example:
-/// ```
+/// ```ignore
 /// make_registers! {
 ///     REG0 => 0,
 ///     REG1 => 4,
@@ -21,25 +22,48 @@ macro_rules! make_registers {
             $register: ident => $address: expr
         ),+
     } => {
-        #[derive(Copy, Clone, Debug)]
+        #[allow(clippy::upper_case_acronyms)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
         pub enum Register {
             $($register,)+
         }
 
+        #[allow(clippy::erasing_op, clippy::identity_op)]
         impl Register {
+            pub const ALL: &'static [Register] = &[$(Register::$register),+];
+
             pub fn as_addr(&self) -> u32 {
                 match self {
                     $(Register::$register => $address),+
                 }
             }
 
-            pub fn from_addr(addr: u32) -> Self {
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Register::$register => stringify!($register)),+
+                }
+            }
+
+            pub fn from_name(name: &str) -> Option<Self> {
+                Register::ALL
+                    .iter()
+                    .copied()
+                    .find(|register| register.name().eq_ignore_ascii_case(name))
+            }
+
+            pub fn from_addr(addr: u32) -> Result<Self, crate::vm::error::Fault> {
                 match addr {
-                    $(addr if addr == $address => Register::$register),+,
-                    _ => panic!("Invalid register address"),
+                    $(addr if addr == $address => Ok(Register::$register)),+,
+                    _ => Err(crate::vm::error::Fault::InvalidRegister(addr)),
                 }
             }
         }
+
+        impl std::fmt::Display for Register {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "{}", self.name())
+            }
+        }
     }
 }
 
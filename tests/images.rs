@@ -0,0 +1,74 @@
+//! Golden-output tests over the sample images in `images/`.
+//!
+//! Every image runs on a private copy-on-write mapping with a scripted
+//! display. The input is taken from `tests/golden/<name>.in` (empty if there
+//! is no such file) unless given explicitly, and the captured output is
+//! compared with `tests/golden/<name>.out`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use toy_vmachine::{Controller, CopyOnWriteMemory, Outcome, ScriptedDisplay, State, VmError};
+
+/// Budget that keeps a broken image from hanging the test suite.
+const MAX_STEPS: u64 = 1_000_000;
+
+fn root() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+fn golden_path(name: &str, extension: &str) -> PathBuf {
+    root()
+        .join("tests/golden")
+        .join(name)
+        .with_extension(extension)
+}
+
+/// Runs `images/<name>.bin`, checks its output against the fixture
+/// and that the image file has not been changed.
+fn run_golden(name: &str, input_path: Option<&Path>) -> Result<Outcome, VmError> {
+    let image_path = root().join("images").join(name).with_extension("bin");
+    let input_path = input_path
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| golden_path(name, "in"));
+    let input = fs::read_to_string(input_path).unwrap_or_default();
+    let expected = fs::read_to_string(golden_path(name, "out")).unwrap();
+    let image = fs::read(&image_path).unwrap();
+
+    let state = State::new(CopyOnWriteMemory::new(&image_path).unwrap()).unwrap();
+    let display = ScriptedDisplay::scripted(&input);
+    let output = display.output();
+    let mut controller = Controller::builder(state)
+        .display(display)
+        .max_steps(MAX_STEPS)
+        .build();
+    let result = controller.execute();
+
+    assert_eq!(output.contents(), expected, "output of {}", name);
+    assert_eq!(
+        fs::read(&image_path).unwrap(),
+        image,
+        "{} was modified",
+        name
+    );
+    result
+}
+
+#[test]
+fn hello_world() {
+    assert_eq!(run_golden("hello_world", None), Ok(Outcome::Finished));
+}
+
+#[test]
+fn fibonacci() {
+    let input_path = root().join("images/input.txt");
+    assert_eq!(
+        run_golden("fibonacci", Some(&input_path)),
+        Ok(Outcome::Finished)
+    );
+}
+
+#[test]
+fn echo() {
+    assert_eq!(run_golden("echo", None), Ok(Outcome::Finished));
+}